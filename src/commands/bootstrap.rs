@@ -25,8 +25,7 @@ pub struct BootstrapArgs {
 }
 
 pub fn run(args: BootstrapArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
 
     let project_root = llmd
         .parent()
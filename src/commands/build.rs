@@ -2,6 +2,8 @@
 //!
 //! Generates a temporary mdbook project (book.toml + src/) from the .llmd/
 //! directory, then calls `mdbook build`. Output goes to .llmd/book/ by default.
+//! Pages are written with frontmatter stripped and `{{#include}}` directives
+//! resolved; a topic's frontmatter `title` names its chapter.
 //! Requires mdbook to be installed: `cargo install mdbook`
 
 use anyhow::{Context, Result, bail};
@@ -12,7 +14,9 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::issues;
+use crate::issues::models::Resolution;
 use crate::llmd_dir;
+use crate::topic::{self, TopicMeta};
 
 #[derive(Parser)]
 pub struct BuildArgs {
@@ -24,8 +28,7 @@ pub struct BuildArgs {
 pub fn run(args: BuildArgs) -> Result<()> {
     ensure_mdbook()?;
 
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let book_dir = generate_mdbook(&llmd)?;
 
     let status = Command::new("mdbook")
@@ -55,9 +58,18 @@ pub fn run(args: BuildArgs) -> Result<()> {
 /// Returns the path to the generated mdbook project root (inside .llmd/.mdbook/).
 /// This is also called by `serve` to reuse the same generation logic.
 pub fn generate_mdbook(llmd: &Path) -> Result<PathBuf> {
+    Ok(update_mdbook(llmd)?.0)
+}
+
+/// Like [`generate_mdbook`], but only rewrites files whose content changed and
+/// removes pages whose source is gone. Returns the project root and the number
+/// of files written or removed; `llmd watch` uses this so `mdbook serve` only
+/// sees real changes.
+pub fn update_mdbook(llmd: &Path) -> Result<(PathBuf, usize)> {
     let book_root = llmd.join(".mdbook");
     let src_dir = book_root.join("src");
     fs::create_dir_all(&src_dir).context("Failed to create mdbook src/ directory")?;
+    let mut out = Output::default();
 
     let all_files = llmd_dir::list_all_files(llmd);
     let catme = llmd_dir::catme_path(llmd);
@@ -65,7 +77,8 @@ pub fn generate_mdbook(llmd: &Path) -> Result<PathBuf> {
     let mut summary = String::from("# Summary\n\n");
 
     if catme.exists() {
-        fs::copy(&catme, src_dir.join("catme.md")).context("Failed to copy catme.md")?;
+        let content = topic::load(llmd, &catme)?.body;
+        out.write(&src_dir.join("catme.md"), &content)?;
         summary.push_str("- [Overview](catme.md)\n");
     }
 
@@ -75,7 +88,11 @@ pub fn generate_mdbook(llmd: &Path) -> Result<PathBuf> {
         if *file == catme {
             continue;
         }
-        if file.starts_with(llmd.join(".mdbook")) || file.starts_with(llmd.join("book")) {
+        if file.starts_with(llmd.join(".mdbook"))
+            || file.starts_with(llmd.join("book"))
+            || file.starts_with(llmd_dir::snapshots_path(llmd))
+            || file.starts_with(llmd_dir::packs_path(llmd))
+        {
             continue;
         }
         let rel = file.strip_prefix(llmd).unwrap_or(file);
@@ -95,32 +112,33 @@ pub fn generate_mdbook(llmd: &Path) -> Result<PathBuf> {
             if issues_dir.is_dir() {
                 let roadmap_md = generate_roadmap_page(llmd, &issues_dir)?;
                 let issues_src = src_dir.join("issues");
-                fs::create_dir_all(&issues_src).context("Failed to create issues src dir")?;
-                fs::write(issues_src.join("roadmap.md"), &roadmap_md)
-                    .context("Failed to write roadmap.md")?;
+                out.write(&issues_src.join("roadmap.md"), &roadmap_md)?;
                 summary.push_str("- [Roadmap](issues/roadmap.md)\n");
             }
         }
         for file in files {
             let rel = file.strip_prefix(llmd).unwrap_or(file);
-            let title = file
+            let stem = file
                 .file_stem()
                 .and_then(|s| s.to_str())
                 .unwrap_or("untitled");
             let rel_str = rel.display().to_string();
             let dest = src_dir.join(rel);
-            if let Some(parent) = dest.parent() {
-                fs::create_dir_all(parent).ok();
+            let topic = topic::load_lenient(llmd, file)?;
+            let mut page = topic.body;
+            if let Some(footer) = meta_footer(&topic.meta) {
+                page.push_str(&footer);
             }
-            fs::copy(file, &dest).with_context(|| format!("Failed to copy {}", file.display()))?;
-            if dir_key == "issues" && title == "roadmap" {
+            out.write(&dest, &page)?;
+            if dir_key == "issues" && stem == "roadmap" {
                 continue;
             }
+            let title = topic.meta.title.as_deref().unwrap_or(stem);
             summary.push_str(&format!("- [{title}]({rel_str})\n"));
         }
     }
 
-    fs::write(src_dir.join("SUMMARY.md"), &summary).context("Failed to write SUMMARY.md")?;
+    out.write(&src_dir.join("SUMMARY.md"), &summary)?;
 
     let project_name = llmd
         .parent()
@@ -132,13 +150,77 @@ pub fn generate_mdbook(llmd: &Path) -> Result<PathBuf> {
         "[book]\ntitle = \"{project_name} — llmd\"\nsrc = \"src\"\n\n\
          [output.html]\nno-section-label = true\n"
     );
-    fs::write(book_root.join("book.toml"), book_toml).context("Failed to write book.toml")?;
+    out.write(&book_root.join("book.toml"), &book_toml)?;
+    out.prune(&src_dir)?;
+
+    Ok((book_root, out.changed))
+}
 
-    Ok(book_root)
+/// Tracks the files written by one mdbook generation pass.
+#[derive(Default)]
+struct Output {
+    written: std::collections::HashSet<PathBuf>,
+    changed: usize,
+}
+
+impl Output {
+    /// Writes `content` to `path` unless the file already holds exactly that.
+    fn write(&mut self, path: &Path, content: &str) -> Result<()> {
+        self.written.insert(path.to_path_buf());
+        if fs::read_to_string(path).is_ok_and(|old| old == content) {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        self.changed += 1;
+        Ok(())
+    }
+
+    /// Removes pages under `src_dir` that this pass did not write.
+    fn prune(&mut self, src_dir: &Path) -> Result<()> {
+        for entry in walkdir::WalkDir::new(src_dir).into_iter().flatten() {
+            let path = entry.path();
+            if entry.file_type().is_file() && !self.written.contains(path) {
+                fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+                self.changed += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders a topic's frontmatter (owners, tags, sources, review date) as a page
+/// footer, or `None` if there is nothing to show.
+fn meta_footer(meta: &TopicMeta) -> Option<String> {
+    let mut parts = Vec::new();
+    if !meta.owners.is_empty() {
+        parts.push(format!("Owners: {}", meta.owners.join(", ")));
+    }
+    if !meta.tags.is_empty() {
+        parts.push(format!("Tags: {}", meta.tags.join(", ")));
+    }
+    if !meta.source_paths.is_empty() {
+        let paths: Vec<String> = meta.source_paths.iter().map(|p| format!("`{p}`")).collect();
+        parts.push(format!("Sources: {}", paths.join(", ")));
+    }
+    if let Some(date) = &meta.last_reviewed {
+        parts.push(format!("Last reviewed: {date}"));
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(format!("\n---\n\n_{}_\n", parts.join(" · ")))
 }
 
 fn generate_roadmap_page(_llmd: &Path, issues_dir: &Path) -> Result<String> {
     let issues_map = issues::load_all_issues(issues_dir).unwrap_or_default();
+    let workflow = issues::load_config(issues_dir)
+        .map(|c| c.workflow)
+        .unwrap_or_default();
     let now = issues::now_iso();
     let date = now.split('T').next().unwrap_or("");
 
@@ -184,9 +266,13 @@ fn generate_roadmap_page(_llmd: &Path, issues_dir: &Path) -> Result<String> {
                 for child_id in &i.epic_children {
                     if let Some(c) = issues_map.get(child_id) {
                         let assignee = c.assignee.as_deref().unwrap_or("—");
+                        let status = match &c.resolution {
+                            Some(r) => format!("{} ({r})", c.status),
+                            None => c.status.clone(),
+                        };
                         md.push_str(&format!(
                             "  - [#{} {}]({:03}-{}.md) `{}` · {} · {}\n",
-                            c.id, c.title, c.id, c.slug, c.issue_type, c.status, assignee
+                            c.id, c.title, c.id, c.slug, c.issue_type, status, assignee
                         ));
                     }
                 }
@@ -209,16 +295,37 @@ fn generate_roadmap_page(_llmd: &Path, issues_dir: &Path) -> Result<String> {
         }
     }
 
-    let closed = issues_map.values().filter(|i| i.status == "closed").count();
-    let total = issues_map.len();
-    let pct = if total > 0 { (closed * 100) / total } else { 0 };
+    // Issues closed as won't fix or duplicate leave the plan instead of
+    // counting as progress.
+    let dropped = issues_map
+        .values()
+        .filter(|i| {
+            workflow.is_terminal(&i.status)
+                && matches!(
+                    i.resolution(),
+                    Some(Resolution::WontFix | Resolution::Duplicate(_))
+                )
+        })
+        .count();
+    let closed = issues_map
+        .values()
+        .filter(|i| workflow.is_terminal(&i.status))
+        .count()
+        - dropped;
+    let total = issues_map.len() - dropped;
+    let pct = (closed * 100).checked_div(total).unwrap_or(0);
     let bar_len = 10;
     let filled = (pct * bar_len) / 100;
     let bar: String = "█".repeat(filled) + &"░".repeat(bar_len - filled);
     md.push_str(&format!(
-        "### Progress\n\n{}/{} closed ({}%)\n\n{}",
+        "### Progress\n\n{}/{} done ({}%)\n\n{}",
         closed, total, pct, bar
     ));
+    if dropped > 0 {
+        md.push_str(&format!(
+            "\n\n{dropped} issue(s) closed as won't fix or duplicate are not counted."
+        ));
+    }
     Ok(md)
 }
 
@@ -3,14 +3,41 @@
 //! Use `llmd index` first to view the section index, then pass section numbers
 //! via `--sections`. With `--issue`, topics are auto-included from the label-to-topics
 //! mapping in `.llmd/context-mappings.json` unless `--no-auto-include` is set.
+//!
+//! Sections can declare prerequisites with `<!-- llmd:requires file#section -->`.
+//! Chosen sections pull their prerequisites in transitively, each placed before
+//! the sections that need it, unless `--no-deps` is set. A `requires:` list in a
+//! topic file's frontmatter applies to every section of that file.
+//!
+//! The `--depth`, `--include-imported` and `--exclude` flags (or the `index`
+//! settings in `.llmd/llmd.json`) change which sections are indexed; pass the
+//! same flags to `llmd index` and `llmd compose` so section numbers agree.
+//!
+//! Topic frontmatter also drives selection: `--tag` includes every topic file
+//! carrying one of the tags, and `--issue` auto-includes topics whose tags match
+//! the issue's labels.
+//!
+//! `--tokens` reports the document's size and `--budget` refuses to emit a
+//! document larger than the given number of tokens, naming the largest
+//! chosen sections. Both count with the tokenizer selected in `llmd.json`.
+//!
+//! `--snapshot` records the document and its [`Inputs`] under
+//! `.llmd/snapshots/` (see `llmd snapshot`). `--since <git-ref|snapshot>`
+//! keeps only the chosen sections, included topics and overview that changed
+//! since then, optionally as unified diffs.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::{llmd_dir, markdown};
+use crate::config::{ComposeConfig, IndexConfig};
+use crate::since::{self, Baseline};
+use crate::tokenizer::Tokenizer;
+use crate::topic::{self, Topic, TopicMeta};
+use crate::{diff, issues, llmd_dir, markdown, usage};
 
 #[derive(Parser)]
 pub struct ComposeArgs {
@@ -29,10 +56,18 @@ pub struct ComposeArgs {
     #[arg(long)]
     pub no_auto_include: bool,
 
+    /// Do not pull in sections declared as prerequisites with `llmd:requires`
+    #[arg(long)]
+    pub no_deps: bool,
+
     /// Explicitly include these topic files by name (no .md, comma-separated)
     #[arg(long, short = 'I', value_delimiter = ',')]
     pub include: Vec<String>,
 
+    /// Include every topic file whose frontmatter `tags` contain one of these (comma-separated)
+    #[arg(long, short = 't', value_delimiter = ',')]
+    pub tag: Vec<String>,
+
     /// Read the task description from a file
     #[arg(long, short, value_name = "FILE")]
     pub from: Option<PathBuf>,
@@ -40,146 +75,505 @@ pub struct ComposeArgs {
     /// Write the composed document to a file instead of stdout
     #[arg(long, short, value_name = "FILE")]
     pub output: Option<PathBuf>,
+
+    /// Print the composed document's token count to stderr
+    #[arg(long, short = 'T')]
+    pub tokens: bool,
+
+    /// Fail instead of emitting a document larger than this many tokens
+    #[arg(long, value_name = "TOKENS")]
+    pub budget: Option<usize>,
+
+    /// Also save the document and a manifest of its inputs under .llmd/snapshots/
+    #[arg(long)]
+    pub snapshot: bool,
+
+    /// Emit only chosen sections, included topics and overview changed since this git revision or snapshot id
+    #[arg(long, value_name = "REF", conflicts_with = "snapshot")]
+    pub since: Option<String>,
+
+    /// With --since, show each changed section as a unified diff
+    #[arg(long, requires = "since")]
+    pub diff: bool,
+
+    #[command(flatten)]
+    pub scope: ScopeArgs,
 }
 
-pub fn run(args: ComposeArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+/// Flags that change which sections make up the index. Shared by `llmd index`
+/// and `llmd compose` so that section numbers agree between the two; each flag
+/// overrides the matching `index` setting in `.llmd/llmd.json`.
+#[derive(Parser)]
+pub struct ScopeArgs {
+    /// Deepest heading level to index, 2–4 (default: 3)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(2..=4))]
+    pub depth: Option<u8>,
 
-    let catme_path = llmd_dir::catme_path(&llmd);
-    let catme =
-        fs::read_to_string(&catme_path).context("Cannot read catme.md — run `llmd init` first")?;
+    /// Also index files in imported/ (e.g. imported CLAUDE.md and AGENTS.md)
+    #[arg(long)]
+    pub include_imported: bool,
 
-    let catme_excerpt = extract_catme_excerpt(&catme);
-    let all_files = llmd_dir::list_all_files(&llmd);
-    let index = build_section_index(&llmd, &all_files);
+    /// Leave files matching this glob (relative to .llmd/) out of the index. Repeatable
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+}
+
+impl ScopeArgs {
+    /// Applies these flags on top of the project's `index` config.
+    pub fn resolve(&self, config: &IndexConfig) -> IndexConfig {
+        let mut scope = config.clone();
+        if let Some(depth) = self.depth {
+            scope.depth = usize::from(depth);
+        }
+        scope.include_imported |= self.include_imported;
+        scope.exclude.extend(self.exclude.iter().cloned());
+        scope
+    }
+}
+
+pub fn run(args: ComposeArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+
+    let config = llmd_dir::config(&llmd)?;
+    let scope = args.scope.resolve(&config.index);
+    let all_files = llmd_dir::list_layered_files(&llmd)?;
+    let index = build_section_index(&llmd, &all_files, &scope)?;
 
     // Resolve sections from --sections (indices into the index)
-    let chosen_sections = resolve_sections_from_indices(&index, &args.sections)?;
+    let chosen = resolve_sections_from_indices(&index, &args.sections)?;
+    let inputs = Inputs {
+        task: if args.issue.is_some() {
+            String::new()
+        } else {
+            load_task(&args).unwrap_or_default()
+        },
+        sections: chosen.iter().map(|&i| index[i].label.clone()).collect(),
+        issue: args.issue.clone(),
+        no_auto_include: args.no_auto_include,
+        no_deps: args.no_deps,
+        include: args.include.clone(),
+        tag: args.tag.clone(),
+        scope,
+    };
+    let since = match &args.since {
+        Some(reference) => Some(Since {
+            baseline: Baseline::resolve(&llmd, reference)?,
+            diff: args.diff,
+        }),
+        None => None,
+    };
+    let composed = compose_with(&llmd, &all_files, &index, &inputs, since.as_ref())?;
+    let doc = &composed.document;
+
+    if args.tokens || args.budget.is_some() {
+        let tokenizer = Tokenizer::for_project(&llmd)?;
+        let count = tokenizer.count(doc);
+        if args.tokens {
+            eprintln!("{}", tokenizer.describe(count));
+        }
+        if let Some(budget) = args.budget
+            && count > budget
+        {
+            bail!(
+                "Composed document is {} — over the budget of {budget}.\n{}",
+                tokenizer.describe(count),
+                largest_sections(&composed.sections, &tokenizer)
+            );
+        }
+    }
+
+    let mut touched: Vec<String> = composed.sections.iter().map(|s| s.label.clone()).collect();
+    touched.extend(composed.includes.iter().cloned());
+    usage::record(&llmd, "compose", touched, doc);
+
+    if args.snapshot {
+        let id = super::snapshot::save(&llmd, &inputs, &composed)?;
+        eprintln!("Saved snapshot {id}");
+    }
+
+    match &args.output {
+        Some(out_path) => {
+            fs::write(out_path, doc)
+                .with_context(|| format!("Cannot write to {}", out_path.display()))?;
+            eprintln!("Wrote context document to {}", out_path.display());
+        }
+        None => print!("{doc}"),
+    }
+
+    Ok(())
+}
+
+/// Everything that determines a composed document, however it was requested.
+/// Stored in snapshot manifests so `llmd snapshot replay` can compose again.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Inputs {
+    /// Task description (empty when composing from an issue).
+    pub task: String,
+    /// Labels of the explicitly chosen sections, before prerequisites.
+    pub sections: Vec<String>,
+    pub issue: Option<String>,
+    pub no_auto_include: bool,
+    pub no_deps: bool,
+    pub include: Vec<String>,
+    pub tag: Vec<String>,
+    /// The index scope after applying flags to the config.
+    pub scope: IndexConfig,
+}
+
+/// A composed document and what went into it.
+pub struct Composed {
+    pub document: String,
+    /// Sections in the document, prerequisites included, in order.
+    pub sections: Vec<IndexedSection>,
+    /// Topic names included in full (`--include`, `--tag` and issue labels).
+    pub includes: Vec<String>,
+    /// Every file the document was built from, included files too.
+    pub files: Vec<PathBuf>,
+}
+
+/// Composes `inputs` against the current docs.
+pub fn compose(llmd: &Path, inputs: &Inputs) -> Result<Composed> {
+    let all_files = llmd_dir::list_layered_files(llmd)?;
+    let index = build_section_index(llmd, &all_files, &inputs.scope)?;
+    compose_with(llmd, &all_files, &index, inputs, None)
+}
+
+/// `--since`: keep only what changed after `baseline`.
+struct Since {
+    baseline: Baseline,
+    /// Show changed sections as unified diffs.
+    diff: bool,
+}
+
+fn compose_with(
+    llmd: &Path,
+    all_files: &[PathBuf],
+    index: &[IndexedSection],
+    inputs: &Inputs,
+    since: Option<&Since>,
+) -> Result<Composed> {
+    let catme_path = llmd_dir::catme_path(llmd);
+    if !catme_path.is_file() {
+        anyhow::bail!("Cannot read catme.md — run `llmd init` first");
+    }
+    let catme = topic::load(llmd, &catme_path)?.body;
+
+    let config = llmd_dir::config(llmd)?;
+    let catme_excerpt = extract_catme_excerpt(&catme, &config.compose.catme_sections);
+    let scope = &inputs.scope;
+    let mut files = vec![catme_path];
+
+    let mut chosen = Vec::new();
+    for label in &inputs.sections {
+        let i = index
+            .iter()
+            .position(|s| &s.label == label)
+            .with_context(|| format!("Section \"{label}\" is not in the index"))?;
+        chosen.push(i);
+    }
+    if !inputs.no_deps {
+        let expanded = expand_prerequisites(llmd, index, &chosen)?;
+        let added: Vec<&str> = expanded
+            .iter()
+            .filter(|i| !chosen.contains(i))
+            .map(|&i| index[i].label.as_str())
+            .collect();
+        if !added.is_empty() {
+            eprintln!(
+                "Added {} prerequisite section(s): {}",
+                added.len(),
+                added.join(", ")
+            );
+        }
+        chosen = expanded;
+    }
+    let chosen_sections: Vec<IndexedSection> = chosen.iter().map(|&i| index[i].clone()).collect();
+    files.extend(chosen_sections.iter().map(|s| s.file.clone()));
 
     // Auto-include topics from issue labels when --issue is set and --no-auto-include is not
-    let mut include_topics = args.include.clone();
-    let header = if let Some(ref id_or_slug) = args.issue {
-        let (issue_header, auto_topics) =
-            load_issue_context(&llmd, id_or_slug, args.no_auto_include)?;
-        if !args.no_auto_include {
+    let mut include_topics = inputs.include.clone();
+    if !inputs.tag.is_empty() {
+        include_topics.extend(topics_with_tags(llmd, all_files, scope, &inputs.tag)?);
+    }
+    let header = if let Some(ref id_or_slug) = inputs.issue {
+        let (issue_header, auto_topics, issue_path) =
+            load_issue_context(llmd, all_files, scope, id_or_slug, inputs.no_auto_include)?;
+        if !inputs.no_auto_include {
             include_topics.extend(auto_topics);
-            include_topics.sort();
-            include_topics.dedup();
         }
+        files.push(issue_path);
         issue_header
+    } else if inputs.task.is_empty() {
+        String::from("# Task Context\n\n")
     } else {
-        let task = load_task(&args).unwrap_or_default();
-        if task.is_empty() {
-            String::from("# Task Context\n\n")
-        } else {
-            format!("# Task Context\n\n## Task\n\n{task}\n\n")
+        format!("# Task Context\n\n## Task\n\n{}\n\n", inputs.task)
+    };
+
+    include_topics.sort();
+    include_topics.dedup();
+    for topic in &include_topics {
+        if let Some(path) = llmd_dir::find_file(llmd, topic)? {
+            files.push(path);
         }
+    }
+
+    let mut layout = config.compose.clone();
+    let (chosen_sections, include_topics) = match since {
+        Some(since) => changed_since(
+            llmd,
+            since,
+            &catme_excerpt,
+            chosen_sections,
+            include_topics,
+            &mut layout,
+        )?,
+        None => (chosen_sections, include_topics),
     };
 
-    let doc = build_document(
+    let document = build_document(
         &header,
         &catme_excerpt,
         &include_topics,
-        &llmd,
+        llmd,
         &chosen_sections,
+        &layout,
     )?;
 
-    match &args.output {
-        Some(out_path) => {
-            fs::write(out_path, &doc)
-                .with_context(|| format!("Cannot write to {}", out_path.display()))?;
-            eprintln!("Wrote context document to {}", out_path.display());
-        }
-        None => print!("{doc}"),
+    // Files pulled in by `{{#include}}` are inputs too; issue files are not topics.
+    let issues_dir = llmd_dir::issues_path(llmd);
+    let mut included = Vec::new();
+    for file in files.iter().filter(|f| !f.starts_with(&issues_dir)) {
+        included.extend(topic::load_lenient(llmd, file)?.includes);
     }
+    files.extend(included);
 
-    Ok(())
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    Ok(Composed {
+        document,
+        sections: chosen_sections,
+        includes: include_topics,
+        files,
+    })
 }
 
-/// Prints the section index to stdout. Used by `llmd index`.
-pub fn print_section_index(llmd: &Path) -> Result<()> {
-    let all_files = llmd_dir::list_all_files(llmd);
-    let index = build_section_index(llmd, &all_files);
+/// Drops the sections, included topics and overview that are unchanged since
+/// `since.baseline`, printing a summary. With `since.diff`, each remaining
+/// section's text becomes its heading and a unified diff. Files missing from
+/// the baseline count as changed.
+fn changed_since(
+    llmd: &Path,
+    since: &Since,
+    catme_excerpt: &str,
+    sections: Vec<IndexedSection>,
+    includes: Vec<String>,
+    layout: &mut ComposeConfig,
+) -> Result<(Vec<IndexedSection>, Vec<String>)> {
+    let baseline = &since.baseline;
+    let name = baseline.name();
+    let config = llmd_dir::config(llmd)?;
+
+    let catme_path = llmd_dir::catme_path(llmd);
+    let overview_changed = baseline.body(llmd, &catme_path).is_none_or(|old| {
+        extract_catme_excerpt(&old, &config.compose.catme_sections) != catme_excerpt
+    });
+    if !overview_changed {
+        layout.layout.retain(|block| block != "overview");
+    }
 
-    if index.is_empty() {
-        eprintln!("No sections found in .llmd/. Add topic files first.");
-        return Ok(());
+    let total_sections = sections.len();
+    let mut changed = Vec::new();
+    for mut section in sections {
+        let old = baseline
+            .body(llmd, &section.file)
+            .and_then(|old| since::old_section(&old, section.depth, &section.heading));
+        if old
+            .as_deref()
+            .is_some_and(|old| old.trim_end() == section.text.trim_end())
+        {
+            continue;
+        }
+        if since.diff {
+            let patch = diff::unified(
+                old.as_deref().unwrap_or_default(),
+                &section.text,
+                &format!("{}@{name}", section.label),
+                &section.label,
+                3,
+            );
+            let heading = section.text.lines().next().unwrap_or_default();
+            section.text = format!("{heading}\n\n```diff\n{patch}```\n");
+        }
+        changed.push(section);
+    }
+
+    let total_includes = includes.len();
+    let mut changed_includes = Vec::new();
+    for name_or_path in includes {
+        let Some(path) = llmd_dir::find_file(llmd, &name_or_path)? else {
+            continue;
+        };
+        let current = topic::load(llmd, &path)?.body;
+        if baseline.body(llmd, &path).is_none_or(|old| old != current) {
+            changed_includes.push(name_or_path);
+        }
     }
 
-    eprintln!("Available sections — use with `llmd compose --sections <nums>`:\n");
-    for (i, section) in index.iter().enumerate() {
-        println!("[{}] {}", i + 1, section.label);
+    eprintln!(
+        "Since {name}: {} of {total_sections} section(s) and {} of {total_includes} included topic(s) changed; overview {}.",
+        changed.len(),
+        changed_includes.len(),
+        if overview_changed {
+            "changed"
+        } else {
+            "unchanged"
+        }
+    );
+    Ok((changed, changed_includes))
+}
+
+/// The five largest chosen sections, as a hint for what to drop.
+fn largest_sections(sections: &[IndexedSection], tokenizer: &Tokenizer) -> String {
+    if sections.is_empty() {
+        return "No sections were chosen; the size comes from catme.md and included topics."
+            .to_string();
     }
-    Ok(())
+    let mut sized: Vec<(usize, &str)> = sections
+        .iter()
+        .map(|s| (tokenizer.count(&s.text), s.label.as_str()))
+        .collect();
+    sized.sort_by_key(|&(count, _)| std::cmp::Reverse(count));
+    let mut out = String::from("Largest chosen sections:");
+    for (count, label) in sized.iter().take(5) {
+        out.push_str(&format!("\n  {label} ({})", tokenizer.describe(*count)));
+    }
+    out
 }
 
 // --- Section index ---
 
 /// A single entry in the section index: a heading from a topic file.
-struct IndexedSection {
+#[derive(Clone)]
+pub struct IndexedSection {
     /// Display label: "topic-file > Heading Text"
-    label: String,
+    pub label: String,
     /// The file this section lives in
-    file: PathBuf,
-    /// The heading text (used to extract the section)
-    heading: String,
+    pub file: PathBuf,
+    /// The heading text
+    pub heading: String,
+    /// Heading level (2 for H2, 3 for H3, …)
+    pub depth: usize,
+    /// The section's markdown, from its heading to the next heading of equal or higher level
+    pub text: String,
 }
 
-/// Builds a flat ordered list of all H2/H3 headings from all non-catme, non-imported, non-issues files.
-fn build_section_index(llmd: &std::path::Path, all_files: &[PathBuf]) -> Vec<IndexedSection> {
-    let catme = llmd_dir::catme_path(llmd);
-    let imported = llmd.join("imported");
-    let issues = llmd_dir::issues_path(llmd);
-    let mut index = Vec::new();
-
+/// Builds a flat ordered list of the H2 to H`scope.depth` headings in every
+/// file that [`is_topic_file`] accepts.
+///
+/// Headings are read after resolving `{{#include}}` directives, so included
+/// sections are indexed (and composed) as part of the including file. Files
+/// are ordered by their frontmatter `priority` (high first).
+pub fn build_section_index(
+    llmd: &std::path::Path,
+    all_files: &[PathBuf],
+    scope: &IndexConfig,
+) -> Result<Vec<IndexedSection>> {
+    let mut topics = Vec::new();
     for file_path in all_files {
-        if *file_path == catme || file_path.starts_with(&imported) || file_path.starts_with(&issues)
-        {
-            continue;
+        if is_topic_file(llmd, file_path, scope) {
+            topics.push((file_path, topic::load_lenient(llmd, file_path)?));
         }
-        let content = match fs::read_to_string(file_path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let file_label = file_path
-            .strip_prefix(llmd)
-            .unwrap_or(file_path)
-            .with_extension("")
-            .display()
-            .to_string();
+    }
+    topics.sort_by_key(|(_, t)| t.meta.priority_rank());
+
+    let mut index = Vec::new();
+    for (file_path, topic) in topics {
+        let file_label = file_label(llmd, file_path);
 
-        for (depth, heading) in markdown::list_headings(&content) {
-            // Include H2 and H3 only — H1 is the file title (too broad),
-            // H4+ are too granular for context selection.
-            if depth == 2 || depth == 3 {
+        for section in markdown::sections(&topic.body) {
+            // H1 is the file title (too broad); by default H4+ are too
+            // granular for context selection.
+            if (2..=scope.depth).contains(&section.depth) {
                 index.push(IndexedSection {
-                    label: format!("{file_label} > {heading}"),
+                    label: format!("{file_label} > {}", section.heading),
                     file: file_path.clone(),
-                    heading: heading.clone(),
+                    heading: section.heading,
+                    depth: section.depth,
+                    text: section.text,
                 });
             }
         }
     }
 
-    index
+    Ok(index)
+}
+
+/// Returns true for files that belong in the section index. `catme.md`,
+/// `issues/` and the generated mdbook directories are never indexed;
+/// `imported/` only when `scope.include_imported` is set. Files matching one
+/// of the `scope.exclude` globs are skipped.
+pub fn is_topic_file(llmd: &Path, file: &Path, scope: &IndexConfig) -> bool {
+    // Paths are checked against the file's own layer.
+    let layer = llmd_dir::layer_of(llmd, file).map(|l| l.dir);
+    let llmd = layer.as_deref().unwrap_or(llmd);
+    if file == llmd_dir::catme_path(llmd)
+        || file.starts_with(llmd_dir::issues_path(llmd))
+        || file.starts_with(llmd.join(".mdbook"))
+        || file.starts_with(llmd.join("book"))
+        || (!scope.include_imported && file.starts_with(llmd.join("imported")))
+    {
+        return false;
+    }
+    let rel = file
+        .strip_prefix(llmd)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .replace('\\', "/");
+    !scope
+        .exclude
+        .iter()
+        .any(|pattern| llmd_dir::glob_match(pattern, &rel))
 }
 
-/// Resolves section indices (1-based) to IndexedSection entries.
+/// Returns the names of topic files whose frontmatter `tags` include any of `tags`.
+fn topics_with_tags(
+    llmd: &Path,
+    all_files: &[PathBuf],
+    scope: &IndexConfig,
+    tags: &[String],
+) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for file in all_files {
+        if !is_topic_file(llmd, file, scope) {
+            continue;
+        }
+        let content =
+            fs::read_to_string(file).with_context(|| format!("Cannot read {}", file.display()))?;
+        let (meta, _) = topic::split(&content);
+        if meta.tags.iter().any(|t| tags.contains(t)) {
+            names.push(file_label(llmd, file));
+        }
+    }
+    Ok(names)
+}
+
+/// Returns the index label for a file: its path inside .llmd/ without `.md`,
+/// prefixed with `<layer>:` when it comes from an enclosing workspace layer.
+pub fn file_label(llmd: &Path, file: &Path) -> String {
+    let path = llmd_dir::layer_path(llmd, file);
+    path.strip_suffix(".md").map(str::to_string).unwrap_or(path)
+}
+
+/// Resolves section numbers (1-based) to positions in the index (0-based).
 fn resolve_sections_from_indices(
     index: &[IndexedSection],
     indices: &[usize],
-) -> Result<Vec<IndexedSection>> {
+) -> Result<Vec<usize>> {
     let mut seen = HashSet::new();
     let mut result = Vec::new();
     for &n in indices {
         if n >= 1 && n <= index.len() && seen.insert(n) {
-            let i = n - 1;
-            result.push(IndexedSection {
-                label: index[i].label.clone(),
-                file: index[i].file.clone(),
-                heading: index[i].heading.clone(),
-            });
+            result.push(n - 1);
         } else if n >= 1 && n <= index.len() {
             // duplicate, skip
         } else {
@@ -192,9 +586,149 @@ fn resolve_sections_from_indices(
     Ok(result)
 }
 
-/// Loads context-mappings.json. Returns label -> topics map.
+// --- Section prerequisites ---
+
+/// Visit state for the prerequisite walk.
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+/// Adds the transitive prerequisites of `chosen` and orders the result so that
+/// every section comes after the sections it requires. Otherwise the order of
+/// `chosen` is kept.
+fn expand_prerequisites(
+    llmd: &Path,
+    index: &[IndexedSection],
+    chosen: &[usize],
+) -> Result<Vec<usize>> {
+    let mut walk = PrerequisiteWalk {
+        llmd,
+        index,
+        metas: HashMap::new(),
+        state: HashMap::new(),
+        path: Vec::new(),
+        order: Vec::new(),
+    };
+    for &i in chosen {
+        walk.visit(i)?;
+    }
+    Ok(walk.order)
+}
+
+struct PrerequisiteWalk<'a> {
+    llmd: &'a Path,
+    index: &'a [IndexedSection],
+    metas: HashMap<PathBuf, TopicMeta>,
+    state: HashMap<usize, Visit>,
+    path: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl PrerequisiteWalk<'_> {
+    fn visit(&mut self, i: usize) -> Result<()> {
+        match self.state.get(&i) {
+            Some(Visit::Done) => return Ok(()),
+            Some(Visit::InProgress) => {
+                let start = self.path.iter().position(|&p| p == i).unwrap_or(0);
+                let cycle: Vec<&str> = self.path[start..]
+                    .iter()
+                    .chain(std::iter::once(&i))
+                    .map(|&p| self.index[p].label.as_str())
+                    .collect();
+                anyhow::bail!("Section prerequisite cycle: {}", cycle.join(" → "));
+            }
+            None => {}
+        }
+        self.state.insert(i, Visit::InProgress);
+        self.path.push(i);
+
+        for reference in self.requires(i)? {
+            let j = self.lookup(i, &reference)?;
+            // A file-level `requires` naming one of the file's own sections
+            // applies to that section too; don't treat it as a cycle.
+            if j != i {
+                self.visit(j)?;
+            }
+        }
+
+        self.path.pop();
+        self.state.insert(i, Visit::Done);
+        self.order.push(i);
+        Ok(())
+    }
+
+    /// Returns the prerequisites of section `i`: its file's frontmatter
+    /// `requires` followed by the `llmd:requires` markers inside the section.
+    fn requires(&mut self, i: usize) -> Result<Vec<String>> {
+        let section = &self.index[i];
+        if !self.metas.contains_key(&section.file) {
+            let content = fs::read_to_string(&section.file)
+                .with_context(|| format!("Cannot read {}", section.file.display()))?;
+            let (meta, _) = topic::split(&content);
+            self.metas.insert(section.file.clone(), meta);
+        }
+        let mut refs = self.metas[&section.file].requires.clone();
+        refs.extend(markdown::requires_markers(&section.text));
+        Ok(refs)
+    }
+
+    /// Finds the index entry for a `file#section` reference made from section `from`.
+    fn lookup(&self, from: usize, reference: &str) -> Result<usize> {
+        let from_section = &self.index[from];
+        let (file, anchor) = reference.split_once('#').with_context(|| {
+            format!(
+                "Invalid prerequisite \"{reference}\" in \"{}\": expected file#section",
+                from_section.label
+            )
+        })?;
+        // An unqualified file name means the file in the referring section's
+        // own layer if it is in the index, otherwise the nearest layer that
+        // has it.
+        let mut candidates = Vec::new();
+        if file.is_empty() {
+            candidates.push(file_label(self.llmd, &from_section.file));
+        } else {
+            let file = llmd_dir::pack_label(file.trim_end_matches(".md"));
+            let file = file.as_str();
+            let qualified = file.contains(':');
+            if let Some(layer) = llmd_dir::layer_of(self.llmd, &from_section.file)
+                && !qualified
+            {
+                candidates.push(layer.qualify(file));
+            }
+            candidates.push(file.to_string());
+            if !qualified {
+                for layer in llmd_dir::layers(self.llmd)? {
+                    if !layer.pack {
+                        candidates.push(layer.qualify(file));
+                    }
+                }
+            }
+        }
+        let anchor = markdown::heading_slug(anchor);
+        candidates
+            .iter()
+            .find_map(|file| {
+                self.index.iter().position(|s| {
+                    file_label(self.llmd, &s.file) == *file
+                        && markdown::heading_slug(&s.heading) == anchor
+                })
+            })
+            .with_context(|| {
+                format!(
+                    "Prerequisite \"{reference}\" required by \"{}\" is not in the section index. \
+                     Run `llmd index` to see available sections.",
+                    from_section.label
+                )
+            })
+    }
+}
+
+/// Loads context-mappings.json (`paths.context_mappings`). Returns label -> topics map.
 fn load_context_mappings(llmd: &Path) -> HashMap<String, Vec<String>> {
-    let path = llmd.join("context-mappings.json");
+    let path = llmd_dir::context_mappings_path(llmd);
     let content = match fs::read_to_string(&path) {
         Ok(c) => c,
         Err(_) => return HashMap::new(),
@@ -212,9 +746,11 @@ fn load_context_mappings(llmd: &Path) -> HashMap<String, Vec<String>> {
 /// Loads issue context: formatted header and auto-included topics from label mapping.
 fn load_issue_context(
     llmd: &Path,
+    all_files: &[PathBuf],
+    scope: &IndexConfig,
     id_or_slug: &str,
     no_auto_include: bool,
-) -> Result<(String, Vec<String>)> {
+) -> Result<(String, Vec<String>, PathBuf)> {
     let issues_dir = llmd_dir::issues_path(llmd);
     if !issues_dir.is_dir() {
         anyhow::bail!(
@@ -222,7 +758,7 @@ fn load_issue_context(
         );
     }
 
-    let issue_path = resolve_issue_file(&issues_dir, id_or_slug)
+    let issue_path = issues::resolve_issue_path(&issues_dir, id_or_slug)
         .with_context(|| format!("Issue \"{id_or_slug}\" not found in .llmd/issues/"))?;
 
     let content = fs::read_to_string(&issue_path)
@@ -240,39 +776,25 @@ fn load_issue_context(
                 topics.extend(mapped.iter().cloned());
             }
         }
+        topics.extend(topics_with_tags(llmd, all_files, scope, &labels)?);
         topics.sort();
         topics.dedup();
         topics
     };
 
-    Ok((header, auto_topics))
-}
-
-/// Resolves id or slug to an issue file path.
-fn resolve_issue_file(issues_dir: &Path, id_or_slug: &str) -> Option<PathBuf> {
-    let entries = fs::read_dir(issues_dir).ok()?;
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("md") {
-            continue;
-        }
-        let stem = path.file_stem().and_then(|s| s.to_str())?;
-        if let Ok(id) = id_or_slug.parse::<u32>()
-            && stem.starts_with(&format!("{id:03}-"))
-        {
-            return Some(path);
-        }
-        if stem.ends_with(&format!("-{id_or_slug}")) || stem == id_or_slug {
-            return Some(path);
-        }
-    }
-    None
+    Ok((header, auto_topics, issue_path))
 }
 
 /// Parses issue frontmatter to extract labels and build a formatted header.
 fn parse_issue_frontmatter(content: &str, id_or_slug: &str) -> (Vec<String>, String) {
-    let (labels, title) = extract_labels_and_title_from_frontmatter(content);
-    let body = extract_issue_body(content);
+    let (labels, title, body) = match issues::parse_issue(content, 0) {
+        Some(issue) => (
+            issue.labels.into_iter().map(|l| l.name).collect(),
+            issue.title,
+            issues::comments::join(&issue.body, &issue.comments).unwrap_or(issue.body),
+        ),
+        None => (Vec::new(), "Untitled".to_string(), String::new()),
+    };
 
     let labels_str = if labels.is_empty() {
         String::new()
@@ -289,111 +811,57 @@ fn parse_issue_frontmatter(content: &str, id_or_slug: &str) -> (Vec<String>, Str
 
 {body}
 
-"#,
-        title = title.unwrap_or_else(|| "Untitled".to_string()),
-        labels_str = labels_str,
-        body = body.unwrap_or_default()
+"#
     );
 
     (labels, header)
 }
 
-/// Extracts label names and title from YAML frontmatter.
-fn extract_labels_and_title_from_frontmatter(content: &str) -> (Vec<String>, Option<String>) {
-    let labels = extract_labels_from_frontmatter(content);
-    let title = content
-        .strip_prefix("---\n")
-        .and_then(|s| s.split("\n---").next())
-        .and_then(|fm| {
-            fm.lines()
-                .find(|l| l.trim_start().starts_with("title:"))
-                .and_then(|l| l.split_once(':'))
-                .map(|(_, v)| v.trim().trim_matches('"').trim_matches('\'').to_string())
-        })
-        .filter(|s| !s.is_empty());
-    (labels, title)
-}
-
-/// Extracts label names from YAML frontmatter. Supports both `labels: [a, b]` and `labels:\n  - name: a`.
-fn extract_labels_from_frontmatter(content: &str) -> Vec<String> {
-    let Some(fm) = content
-        .strip_prefix("---\n")
-        .and_then(|s| s.split("\n---").next())
-    else {
-        return Vec::new();
-    };
-
-    let mut labels = Vec::new();
-    let mut in_labels = false;
-    let mut indent = 0;
-
-    for line in fm.lines() {
-        if let Some(rest) = line.strip_prefix("labels:") {
-            let rest = rest.trim();
-            if let Some(bracket) = rest.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
-                for part in bracket.split(',') {
-                    let label = part.trim().trim_matches('"').trim_matches('\'').to_string();
-                    if !label.is_empty() {
-                        labels.push(label);
-                    }
-                }
-            }
-            in_labels = true;
-            indent = line.len() - line.trim_start().len();
-        } else if in_labels {
-            let line_indent = line.len() - line.trim_start().len();
-            if line_indent <= indent && !line.trim().is_empty() {
-                in_labels = false;
-            } else if line.contains("name:")
-                && let Some(after) = line.split("name:").nth(1)
-            {
-                let name = after
-                    .split_whitespace()
-                    .next()
-                    .unwrap_or("")
-                    .trim_matches('"')
-                    .trim_matches('\'')
-                    .to_string();
-                if !name.is_empty() {
-                    labels.push(name);
-                }
-            }
-        }
-    }
-    labels
-}
-
-/// Extracts the body (markdown after frontmatter) from issue content.
-fn extract_issue_body(content: &str) -> Option<String> {
-    content
-        .strip_prefix("---\n")
-        .and_then(|s| s.split("\n---\n").nth(1))
-        .map(|s| s.trim().to_string())
-}
-
 // --- Document assembly ---
 
+/// Assembles the document: the task header, then the overview, included
+/// files and selected sections in the order given by `compose.layout`.
 fn build_document(
     header: &str,
     catme_excerpt: &str,
     include_files: &[String],
     llmd: &std::path::Path,
     sections: &[IndexedSection],
+    compose: &ComposeConfig,
 ) -> Result<String> {
     let mut doc = String::new();
 
     doc.push_str(header);
-    doc.push_str("## Project Overview\n\n");
-    doc.push_str(catme_excerpt);
-    doc.push('\n');
+    for block in &compose.layout {
+        match block.as_str() {
+            "overview" => {
+                doc.push_str("## Project Overview\n\n");
+                doc.push_str(catme_excerpt);
+                doc.push('\n');
+            }
+            "includes" => push_includes(&mut doc, include_files, llmd)?,
+            "sections" => push_sections(&mut doc, sections, llmd),
+            _ => {}
+        }
+    }
+
+    Ok(doc)
+}
 
-    // Explicitly included full files (--include flag)
+/// Explicitly included full files (--include flag)
+fn push_includes(doc: &mut String, include_files: &[String], llmd: &Path) -> Result<()> {
     for topic in include_files {
-        let path = llmd.join(format!("{topic}.md"));
-        if path.exists() {
-            let content =
-                fs::read_to_string(&path).with_context(|| format!("Cannot read {topic}.md"))?;
-            doc.push_str(&format!("## {topic}\n\n"));
+        if let Some(path) = llmd_dir::find_file(llmd, topic)? {
+            let Topic {
+                meta,
+                body: content,
+                ..
+            } = topic::load(llmd, &path)?;
+            let title = meta.title.as_deref().unwrap_or(topic);
+            doc.push_str(&format!("## {title}\n\n"));
+            if let Some(line) = meta_line(&meta) {
+                doc.push_str(&line);
+            }
             doc.push_str(&content);
             if !content.ends_with('\n') {
                 doc.push('\n');
@@ -401,42 +869,56 @@ fn build_document(
             doc.push('\n');
         }
     }
+    Ok(())
+}
 
-    if !sections.is_empty() {
-        doc.push_str("## Relevant Sections\n\n");
-        // Group sections by file to avoid repeated file headers
-        let mut current_file: Option<&PathBuf> = None;
-        for section in sections {
-            if Some(&section.file) != current_file {
-                let label = section
-                    .file
-                    .strip_prefix(llmd)
-                    .unwrap_or(&section.file)
-                    .with_extension("")
-                    .display()
-                    .to_string();
-                doc.push_str(&format!("### {label}\n\n"));
-                current_file = Some(&section.file);
-            }
-            let content = match fs::read_to_string(&section.file) {
-                Ok(c) => c,
-                Err(_) => continue,
-            };
-            if let Some(text) = markdown::extract_section(&content, &section.heading) {
-                doc.push_str(&text);
-                if !text.ends_with('\n') {
-                    doc.push('\n');
-                }
-                doc.push('\n');
-            }
+fn push_sections(doc: &mut String, sections: &[IndexedSection], llmd: &Path) {
+    if sections.is_empty() {
+        return;
+    }
+    doc.push_str("## Relevant Sections\n\n");
+    // Group sections by file to avoid repeated file headers
+    let mut current_file: Option<&PathBuf> = None;
+    for section in sections {
+        if Some(&section.file) != current_file {
+            let label = file_label(llmd, &section.file);
+            doc.push_str(&format!("### {label}\n\n"));
+            current_file = Some(&section.file);
+        }
+        doc.push_str(&section.text);
+        if !section.text.ends_with('\n') {
+            doc.push('\n');
         }
+        doc.push('\n');
     }
-
-    Ok(doc)
 }
 
 // --- Helpers ---
 
+/// Formats the frontmatter fields useful to an agent (summary, source paths,
+/// owners) as a blockquote, or `None` if the topic declares none of them.
+fn meta_line(meta: &TopicMeta) -> Option<String> {
+    let mut parts = Vec::new();
+    if !meta.source_paths.is_empty() {
+        let paths: Vec<String> = meta.source_paths.iter().map(|p| format!("`{p}`")).collect();
+        parts.push(format!("Sources: {}", paths.join(", ")));
+    }
+    if !meta.owners.is_empty() {
+        parts.push(format!("Owners: {}", meta.owners.join(", ")));
+    }
+    let mut out = String::new();
+    if let Some(summary) = &meta.summary {
+        out.push_str(&format!("> {summary}\n"));
+        if !parts.is_empty() {
+            out.push_str(">\n");
+        }
+    }
+    if !parts.is_empty() {
+        out.push_str(&format!("> {}\n", parts.join(" · ")));
+    }
+    (!out.is_empty()).then(|| out + "\n")
+}
+
 fn load_task(args: &ComposeArgs) -> Result<String> {
     if let Some(path) = &args.from {
         return fs::read_to_string(path)
@@ -453,15 +935,45 @@ fn load_task(args: &ComposeArgs) -> Result<String> {
     }
 }
 
-fn extract_catme_excerpt(catme: &str) -> String {
-    let summary = markdown::extract_section(catme, "Project Summary");
-    let stack = markdown::extract_section(catme, "Technology Stack");
-    let build = markdown::extract_section(catme, "Build");
+/// Quotes the configured catme.md sections (`compose.catme_sections`), in
+/// order, stopping at the first one that is missing. Falls back to the first
+/// 40 lines when the first section is absent.
+pub fn extract_catme_excerpt(catme: &str, headings: &[String]) -> String {
+    let parts: Vec<String> = headings
+        .iter()
+        .map_while(|h| markdown::extract_section(catme, h))
+        .collect();
+    if parts.is_empty() {
+        return catme.lines().take(40).collect::<Vec<_>>().join("\n") + "\n";
+    }
+    parts.join("\n\n") + "\n"
+}
 
-    match (summary, stack, build) {
-        (Some(s), Some(t), Some(b)) => format!("{s}\n\n{t}\n\n{b}\n"),
-        (Some(s), Some(t), None) => format!("{s}\n\n{t}\n"),
-        (Some(s), None, _) => format!("{s}\n"),
-        _ => catme.lines().take(40).collect::<Vec<_>>().join("\n") + "\n",
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_build_section_index_with_broken_include() {
+        let fx = Fixture::new(
+            "compose-index",
+            &[
+                (
+                    "broken.md",
+                    "# Broken\n\n## Shared\n\n{{#include nowhere}}\n",
+                ),
+                ("fine.md", "# Fine\n\n## Works\n\nok\n"),
+            ],
+        );
+        let llmd = fx.llmd();
+        let files = llmd_dir::list_layered_files(&llmd).unwrap();
+        let index = build_section_index(&llmd, &files, &IndexConfig::default()).unwrap();
+        let mut labels: Vec<&str> = index.iter().map(|s| s.label.as_str()).collect();
+        labels.sort();
+        assert_eq!(labels, vec!["broken > Shared", "fine > Works"]);
+        // The broken file is indexed from its unexpanded body.
+        let broken = index.iter().find(|s| s.label == "broken > Shared").unwrap();
+        assert!(broken.text.contains("{{#include nowhere}}"));
     }
 }
@@ -0,0 +1,138 @@
+//! `llmd config` — inspect and edit the project config (`.llmd/llmd.json`).
+//!
+//! Keys are dotted paths into the config, e.g. `index.depth` or
+//! `compose.layout`. `set` parses the value as JSON when it can (numbers,
+//! booleans, lists) and otherwise stores it as a string; a plain string given
+//! for a list key is split on commas. The edited config is validated before it
+//! is written, so an invalid value never reaches the file.
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use serde_json::{Map, Value};
+
+use crate::{config, llmd_dir};
+
+#[derive(Parser)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective config (file values merged over defaults) as JSON
+    Show,
+    /// Print the effective value of one key, e.g. `index.depth`
+    Get {
+        /// Dotted key
+        key: String,
+    },
+    /// Set a key in .llmd/llmd.json, creating the file if needed
+    Set {
+        /// Dotted key
+        key: String,
+        /// New value (JSON, or a plain string; lists accept `a,b,c`)
+        value: String,
+    },
+    /// Print the JSON Schema for .llmd/llmd.json
+    Schema,
+}
+
+pub fn run(args: ConfigArgs) -> Result<()> {
+    match args.command {
+        ConfigCommand::Schema => {
+            println!("{}", serde_json::to_string_pretty(&config::schema())?);
+        }
+        ConfigCommand::Show => {
+            let llmd = llmd_dir::locate_unvalidated()?;
+            match config::load(&llmd) {
+                Ok(config) => {
+                    let effective = serde_json::to_value(config)?;
+                    println!("{}", serde_json::to_string_pretty(&effective)?);
+                }
+                // Show what is there, so it can be fixed with `config set`.
+                Err(e) => {
+                    eprintln!("Warning: {e:#}; showing the file as written.");
+                    let path = config::path(&llmd);
+                    let content = std::fs::read_to_string(&path)
+                        .with_context(|| format!("Cannot read {}", path.display()))?;
+                    print!("{content}");
+                }
+            }
+        }
+        ConfigCommand::Get { key } => {
+            let llmd = llmd_dir::locate()?;
+            let effective = serde_json::to_value(llmd_dir::config(&llmd)?)?;
+            let value = lookup(&effective, &key)?;
+            match value {
+                Value::String(s) => println!("{s}"),
+                other => println!("{}", serde_json::to_string_pretty(other)?),
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            // `update` validates the edited file, so a broken one can be repaired.
+            let llmd = llmd_dir::locate_unvalidated()?;
+            let defaults = serde_json::to_value(config::Config::default())?;
+            let current = lookup(&defaults, &key)?;
+            let value = parse_value(&value, current);
+
+            config::update(&llmd, |file| insert(file, &key, value))?;
+            eprintln!("Updated {key} in {}", config::path(&llmd).display());
+        }
+    }
+    Ok(())
+}
+
+/// Looks up a dotted key, failing with the list of valid keys at that level.
+fn lookup<'a>(root: &'a Value, key: &str) -> Result<&'a Value> {
+    let mut value = root;
+    for part in key.split('.') {
+        value = match value.get(part) {
+            Some(v) => v,
+            None => {
+                let known = value
+                    .as_object()
+                    .map(|m| m.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default();
+                bail!("Unknown config key \"{key}\" (expected one of: {known})");
+            }
+        };
+    }
+    Ok(value)
+}
+
+/// Parses a command-line value, using the key's default to interpret plain strings.
+fn parse_value(raw: &str, default: &Value) -> Value {
+    if let Ok(v) = serde_json::from_str::<Value>(raw) {
+        return v;
+    }
+    if default.is_array() {
+        return Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| Value::String(s.to_string()))
+                .collect(),
+        );
+    }
+    Value::String(raw.to_string())
+}
+
+/// Sets a dotted key in a JSON object, creating intermediate objects.
+fn insert(root: &mut Value, key: &str, value: Value) -> Result<()> {
+    let (parents, last) = match key.rsplit_once('.') {
+        Some((parents, last)) => (parents.split('.').collect::<Vec<_>>(), last),
+        None => (Vec::new(), key),
+    };
+    let mut node = root;
+    for part in parents {
+        let map = node
+            .as_object_mut()
+            .with_context(|| format!("Cannot set \"{key}\": \"{part}\" is not an object"))?;
+        node = map.entry(part).or_insert_with(|| Value::Object(Map::new()));
+    }
+    node.as_object_mut()
+        .with_context(|| format!("Cannot set \"{key}\": parent is not an object"))?
+        .insert(last.to_string(), value);
+    Ok(())
+}
@@ -1,20 +1,259 @@
 //! `llmd index` — print the section index for use with `llmd compose`.
 //!
-//! Outputs a numbered list of all H2/H3 sections from topic files in .llmd/.
+//! Outputs a numbered list of all H2/H3 sections (by default) from topic files in .llmd/.
 //! The caller (human or agent) uses this to choose section numbers to pass to
 //! `llmd compose --sections 1,2,3`.
+//!
+//! `--tree` groups sections under their file with H3s nested under H2s,
+//! `--tokens` adds each section's token count (see `tokenizer`) and `--summary` a one-line
+//! preview. `--json` emits the same information for programmatic selection.
+//! `--depth`, `--include-imported` and `--exclude` change which sections are
+//! indexed (see `compose::ScopeArgs`).
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+
+use crate::commands::compose::{self, IndexedSection, ScopeArgs};
+use crate::tokenizer::Tokenizer;
+use crate::{llmd_dir, markdown, topic};
 
-use crate::commands::compose;
+/// Maximum length of a `--summary` preview, in characters.
+const PREVIEW_CHARS: usize = 100;
 
 #[derive(Parser)]
-pub struct IndexArgs {}
+pub struct IndexArgs {
+    /// Group sections under their file and nest H3 sections under H2 sections
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Show each section's token count (including subsections)
+    #[arg(long, short = 'T')]
+    pub tokens: bool,
+
+    /// Show a one-line preview (the first sentence) of each section
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Print the index as JSON, always including token counts and previews
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub scope: ScopeArgs,
+}
+
+pub fn run(args: IndexArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+
+    let scope = args.scope.resolve(&llmd_dir::config(&llmd)?.index);
+    let all_files = llmd_dir::list_layered_files(&llmd)?;
+    let index = compose::build_section_index(&llmd, &all_files, &scope)?;
+    let tokenizer = if args.tokens || args.json {
+        Tokenizer::for_project(&llmd)?
+    } else {
+        Tokenizer::Heuristic
+    };
 
-pub fn run(_args: IndexArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = crate::llmd_dir::find(&cwd)?;
+    if index.is_empty() && !args.json {
+        eprintln!("No sections found in .llmd/. Add topic files first.");
+        return Ok(());
+    }
+
+    let numbered: Vec<(usize, &IndexedSection)> =
+        index.iter().enumerate().map(|(i, s)| (i + 1, s)).collect();
+
+    if args.json {
+        let out = if args.tree {
+            Value::Array(
+                group_by_file(&numbered)
+                    .into_iter()
+                    .map(|group| file_json(&llmd, group, &tokenizer))
+                    .collect::<Result<_>>()?,
+            )
+        } else {
+            Value::Array(
+                numbered
+                    .iter()
+                    .map(|&(n, s)| {
+                        let mut v = section_json(n, s, &tokenizer);
+                        v["label"] = json!(s.label);
+                        v["file"] = json!(compose::file_label(&llmd, &s.file));
+                        v
+                    })
+                    .collect(),
+            )
+        };
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    eprintln!("Available sections — use with `llmd compose --sections <nums>`:\n");
+    if args.tree {
+        for group in group_by_file(&numbered) {
+            print_file(&llmd, group, &args, &tokenizer)?;
+        }
+    } else {
+        for &(n, section) in &numbered {
+            println!(
+                "[{n}] {}{}",
+                section.label,
+                details(section, &args, &tokenizer)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// A section with the sections nested under it.
+struct Node<'a> {
+    number: usize,
+    section: &'a IndexedSection,
+    children: Vec<Node<'a>>,
+}
+
+/// Splits the index into runs of sections from the same file, keeping order.
+fn group_by_file<'a>(
+    numbered: &'a [(usize, &'a IndexedSection)],
+) -> Vec<&'a [(usize, &'a IndexedSection)]> {
+    numbered
+        .chunk_by(|(_, a), (_, b)| a.file == b.file)
+        .collect()
+}
+
+/// Nests each section under the closest preceding section of lower depth
+/// (H3 under H2, H4 under H3).
+fn nest<'a>(sections: &[(usize, &'a IndexedSection)]) -> Vec<Node<'a>> {
+    let mut nodes = Vec::new();
+    let mut i = 0;
+    while i < sections.len() {
+        let (number, section) = sections[i];
+        let end = sections[i + 1..]
+            .iter()
+            .position(|(_, s)| s.depth <= section.depth)
+            .map_or(sections.len(), |p| i + 1 + p);
+        nodes.push(Node {
+            number,
+            section,
+            children: nest(&sections[i + 1..end]),
+        });
+        i = end;
+    }
+    nodes
+}
+
+fn print_file(
+    llmd: &Path,
+    group: &[(usize, &IndexedSection)],
+    args: &IndexArgs,
+    tokenizer: &Tokenizer,
+) -> Result<()> {
+    let file = group[0].1.file.as_path();
+    let meta = read_meta(file)?;
+    let mut line = compose::file_label(llmd, file);
+    if let Some(title) = &meta.title {
+        line.push_str(&format!(" — {title}"));
+    }
+    if args.tokens {
+        line.push_str(&format!(
+            " ({})",
+            tokenizer.describe(file_tokens(group, tokenizer))
+        ));
+    }
+    println!("{line}");
+    if args.summary
+        && let Some(summary) = &meta.summary
+    {
+        println!("  {summary}");
+    }
+    for node in nest(group) {
+        print_node(&node, 1, args, tokenizer);
+    }
+    Ok(())
+}
+
+fn print_node(node: &Node, level: usize, args: &IndexArgs, tokenizer: &Tokenizer) {
+    println!(
+        "{}[{}] {}{}",
+        "  ".repeat(level),
+        node.number,
+        node.section.heading,
+        details(node.section, args, tokenizer)
+    );
+    for child in &node.children {
+        print_node(child, level + 1, args, tokenizer);
+    }
+}
+
+/// The optional `(N tokens) — preview` suffix for a section line.
+fn details(section: &IndexedSection, args: &IndexArgs, tokenizer: &Tokenizer) -> String {
+    let mut out = String::new();
+    if args.tokens {
+        out.push_str(&format!(
+            " ({})",
+            tokenizer.describe(tokenizer.count(&section.text))
+        ));
+    }
+    if args.summary
+        && let Some(preview) = markdown::first_sentence(&section.text, PREVIEW_CHARS)
+    {
+        out.push_str(&format!(" — {preview}"));
+    }
+    out
+}
+
+/// Tokens for a file: the sum of its top-level indexed sections.
+fn file_tokens(group: &[(usize, &IndexedSection)], tokenizer: &Tokenizer) -> usize {
+    nest(group)
+        .iter()
+        .map(|n| tokenizer.count(&n.section.text))
+        .sum()
+}
+
+fn read_meta(file: &Path) -> Result<topic::TopicMeta> {
+    let content =
+        fs::read_to_string(file).with_context(|| format!("Cannot read {}", file.display()))?;
+    Ok(topic::split(&content).0)
+}
+
+fn section_json(number: usize, section: &IndexedSection, tokenizer: &Tokenizer) -> Value {
+    json!({
+        "index": number,
+        "heading": section.heading,
+        "depth": section.depth,
+        "tokens": tokenizer.count(&section.text),
+        "preview": markdown::first_sentence(&section.text, PREVIEW_CHARS),
+    })
+}
+
+fn node_json(node: &Node, tokenizer: &Tokenizer) -> Value {
+    let mut v = section_json(node.number, node.section, tokenizer);
+    v["children"] = Value::Array(
+        node.children
+            .iter()
+            .map(|child| node_json(child, tokenizer))
+            .collect(),
+    );
+    v
+}
 
-    compose::print_section_index(&llmd)
+fn file_json(
+    llmd: &Path,
+    group: &[(usize, &IndexedSection)],
+    tokenizer: &Tokenizer,
+) -> Result<Value> {
+    let file = group[0].1.file.as_path();
+    let meta = read_meta(file)?;
+    Ok(json!({
+        "file": compose::file_label(llmd, file),
+        "title": meta.title,
+        "summary": meta.summary,
+        "tokens": file_tokens(group, tokenizer),
+        "sections": nest(group)
+            .iter()
+            .map(|node| node_json(node, tokenizer))
+            .collect::<Vec<_>>(),
+    }))
 }
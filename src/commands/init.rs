@@ -8,7 +8,7 @@ use clap::Parser;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::discovery;
+use crate::{discovery, llmd_dir};
 
 #[derive(Parser)]
 pub struct InitArgs {
@@ -16,13 +16,17 @@ pub struct InitArgs {
     #[arg(long)]
     pub update: bool,
 
-    /// Project root to initialise (defaults to current directory)
-    #[arg(default_value = ".")]
-    pub root: PathBuf,
+    /// Project root to initialise (defaults to --root, then the current directory)
+    #[arg(value_name = "ROOT")]
+    pub path: Option<PathBuf>,
 }
 
 pub fn run(args: InitArgs) -> Result<()> {
-    let root = args.root.canonicalize().context("Invalid project root")?;
+    let root = match args.path {
+        Some(path) => path,
+        None => llmd_dir::start_dir()?,
+    };
+    let root = root.canonicalize().context("Invalid project root")?;
     let llmd = root.join(".llmd");
     let imported = llmd.join("imported");
     let catme = llmd.join("catme.md");
@@ -0,0 +1,71 @@
+//! `llmd issue activity`
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::issues::log::{self, Event};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct ActivityArgs {
+    /// Only changes on or after this date (YYYY-MM-DD) or time (ISO 8601)
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Only changes made by this author or agent
+    #[arg(long)]
+    pub author: Option<String>,
+
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: ActivityArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+    if let Some(since) = &args.since
+        && !crate::commands::usage::is_date(since)
+        && iso8601_timestamp::Timestamp::parse(since).is_none()
+    {
+        anyhow::bail!("Invalid --since \"{since}\": expected YYYY-MM-DD or an ISO 8601 time");
+    }
+
+    let (mut events, skipped) = log::load(&issues_dir)?;
+    if skipped > 0 {
+        eprintln!("Warning: skipped {skipped} malformed line(s) in the issue log");
+    }
+    if let Some(since) = &args.since {
+        events.retain(|e| e.ts.as_str() >= since.as_str());
+    }
+    if let Some(author) = &args.author {
+        events.retain(|e| e.author == *author);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+    } else if events.is_empty() {
+        eprintln!("No recorded changes.");
+    } else {
+        for e in &events {
+            println!("{} #{} {} {}", e.ts, e.issue, e.author, describe(e));
+        }
+    }
+    Ok(())
+}
+
+/// `status: open → in_progress`, `created: Fix login` or `comment: [2] Thanks`.
+pub(super) fn describe(event: &Event) -> String {
+    if event.old.is_null() && matches!(event.field.as_str(), "created" | "comment") {
+        return format!("{}: {}", event.field, log::display(&event.new));
+    }
+    format!(
+        "{}: {} → {}",
+        event.field,
+        log::display(&event.old),
+        log::display(&event.new)
+    )
+}
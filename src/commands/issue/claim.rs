@@ -0,0 +1,89 @@
+//! `llmd issue claim`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+
+use crate::issues::frontmatter::parse_issue;
+use crate::issues::{file_ops, load_config, log, resolve_issue_path, write_issue};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct ClaimArgs {
+    pub id_or_slug: String,
+
+    /// Name of the agent taking the issue; becomes the assignee
+    #[arg(long)]
+    pub agent: String,
+
+    /// How long the claim lasts unless renewed: 45s, 30m, 2h, 1d
+    #[arg(long, default_value = "30m")]
+    pub ttl: String,
+}
+
+pub fn run(args: ClaimArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let ttl = file_ops::parse_duration(&args.ttl)?;
+
+    // Check and write under the lock so two agents cannot both see the
+    // issue as free.
+    let _lock = file_ops::lock(&issues_dir)?;
+
+    let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
+        .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+
+    let id: u32 = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.split('-').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let mut issue = parse_issue(&content, id).context("Failed to parse issue")?;
+    let workflow = load_config(&issues_dir)?.workflow;
+
+    if workflow.is_terminal(&issue.status) {
+        anyhow::bail!("Issue #{id} is {}", issue.status);
+    }
+    if issue.has_live_lease() && issue.assignee.as_deref() != Some(args.agent.as_str()) {
+        anyhow::bail!(
+            "Issue #{id} is claimed by {} until {}",
+            issue.assignee.as_deref().unwrap_or("another agent"),
+            issue.lease_expires_at.as_deref().unwrap_or_default()
+        );
+    }
+    let before = issue.clone();
+    if let Some(active) = workflow.active_status() {
+        workflow.check_transition(&issue.status, active)?;
+        issue.status = active.to_string();
+    }
+
+    let expires =
+        file_ops::iso_after(ttl).with_context(|| format!("--ttl \"{}\" is too long", args.ttl))?;
+    issue.assignee = Some(args.agent.clone());
+    // Claiming accepts a pending handoff, or takes over from it.
+    if let Some(to) = issue.handover_to.take()
+        && to != args.agent
+    {
+        eprintln!(
+            "Note: issue #{id} was handed to {to}; {} takes it instead",
+            args.agent
+        );
+    }
+    issue.lease_expires_at = Some(expires.clone());
+    issue.updated_at = file_ops::now_iso();
+    write_issue(&issues_dir, &issue)?;
+    log::record(&issues_dir, Some(&before), &issue, &args.agent);
+
+    eprintln!("Claimed issue #{id} for {} until {expires}", args.agent);
+    Ok(())
+}
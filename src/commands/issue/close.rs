@@ -0,0 +1,94 @@
+//! `llmd issue close`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+
+use super::update::author;
+use crate::issues::frontmatter::parse_issue;
+use crate::issues::models::{self, Resolution};
+use crate::issues::{file_ops, load_all_issues, load_config, log, resolve_issue_path, write_issue};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct CloseArgs {
+    pub id_or_slug: String,
+
+    /// done, wontfix or duplicate:<id>
+    #[arg(long, default_value = "done")]
+    pub resolution: String,
+
+    #[arg(long)]
+    pub comment: Option<String>,
+
+    #[arg(long)]
+    pub author: Option<String>,
+}
+
+pub fn run(args: CloseArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let Some(mut resolution) = Resolution::parse(&args.resolution) else {
+        anyhow::bail!(
+            "Invalid resolution \"{}\": expected done, wontfix or duplicate:<id>",
+            args.resolution
+        );
+    };
+
+    let _lock = file_ops::lock(&issues_dir)?;
+
+    let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
+        .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+
+    let id: u32 = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.split('-').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let mut issue = parse_issue(&content, id).context("Failed to parse issue")?;
+
+    if let Resolution::Duplicate(original) = resolution {
+        // The original may be given by alias; store its id.
+        let original = models::resolve_id(&load_all_issues(&issues_dir)?, original)?;
+        if original == id {
+            anyhow::bail!("Issue #{id} cannot be a duplicate of itself");
+        }
+        resolution = Resolution::Duplicate(original);
+    }
+
+    let workflow = load_config(&issues_dir)?.workflow;
+    let status = workflow.closed_status();
+    workflow.check_transition(&issue.status, status)?;
+
+    let before = issue.clone();
+    let who = author(args.author);
+    let now = file_ops::now_iso();
+    issue.status = status.to_string();
+    issue.resolution = Some(resolution.to_string());
+    issue.closed_at = Some(now.clone());
+    issue.handover_to = None;
+    if let Some(ref comment_text) = args.comment {
+        issue.add_comment(&who, &now, comment_text, None);
+    }
+    issue.updated_at = now;
+    write_issue(&issues_dir, &issue)?;
+    log::record(&issues_dir, Some(&before), &issue, &who);
+
+    match resolution {
+        Resolution::Duplicate(original) => {
+            eprintln!("Closed issue #{id} as a duplicate of #{original}")
+        }
+        _ => eprintln!("Closed issue #{id} ({resolution})"),
+    }
+    Ok(())
+}
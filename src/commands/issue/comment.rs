@@ -0,0 +1,84 @@
+//! `llmd issue comment`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+use std::io::Read;
+
+use super::update::author;
+use crate::issues::frontmatter::parse_issue;
+use crate::issues::{file_ops, log, resolve_issue_path, write_issue};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct CommentArgs {
+    pub id_or_slug: String,
+
+    /// The comment; read from stdin if omitted or `-`
+    pub body: Option<String>,
+
+    /// Id of the comment this one answers
+    #[arg(long, value_name = "COMMENT_ID")]
+    pub reply_to: Option<u32>,
+
+    #[arg(long)]
+    pub author: Option<String>,
+}
+
+pub fn run(args: CommentArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let body = match args.body.as_deref() {
+        Some(body) if body != "-" => body.to_string(),
+        _ => {
+            let mut buf = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buf)
+                .context("Cannot read the comment from stdin")?;
+            buf
+        }
+    };
+    let body = body.trim();
+    if body.is_empty() {
+        anyhow::bail!("The comment is empty");
+    }
+
+    let _lock = file_ops::lock(&issues_dir)?;
+
+    let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
+        .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+
+    let id: u32 = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.split('-').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let mut issue = parse_issue(&content, id).context("Failed to parse issue")?;
+
+    if let Some(parent) = args.reply_to
+        && !issue.comments.iter().any(|c| c.id == parent)
+    {
+        anyhow::bail!("Issue #{id} has no comment {parent}");
+    }
+
+    let before = issue.clone();
+    let who = author(args.author);
+    let now = file_ops::now_iso();
+    let comment_id = issue.add_comment(&who, &now, body, args.reply_to);
+    issue.updated_at = now;
+    write_issue(&issues_dir, &issue)?;
+    log::record(&issues_dir, Some(&before), &issue, &who);
+
+    eprintln!("Added comment {comment_id} to issue #{id}");
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! `llmd issue comments`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+
+use crate::issues::frontmatter::parse_issue;
+use crate::issues::models::Comment;
+use crate::issues::resolve_issue_path;
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct CommentsArgs {
+    pub id_or_slug: String,
+
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: CommentsArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
+        .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+
+    let id: u32 = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.split('-').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let issue = parse_issue(&content, id).context("Failed to parse issue")?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&issue.comments)?);
+    } else {
+        print!("{}", render_threads(&issue.comments));
+    }
+    Ok(())
+}
+
+/// Renders comments as threads: each reply indented under the comment it
+/// answers. Replies to comments that do not exist are shown at the top level.
+fn render_threads(comments: &[Comment]) -> String {
+    let mut out = String::new();
+    let is_root = |c: &Comment| {
+        c.reply_to
+            .is_none_or(|parent| !comments.iter().any(|p| p.id == parent && p.id != c.id))
+    };
+    for root in comments.iter().filter(|c| is_root(c)) {
+        render(comments, root, 0, &mut out);
+    }
+    out
+}
+
+fn render(comments: &[Comment], comment: &Comment, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let handoff = comment
+        .handoff_to
+        .as_ref()
+        .map(|to| format!(" → handoff to {to}"))
+        .unwrap_or_default();
+    out.push_str(&format!(
+        "{indent}[{}] {} ({}){handoff}\n",
+        comment.id, comment.author, comment.date
+    ));
+    for line in comment.body.lines() {
+        let line = format!("{indent}  {line}");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    // The depth limit guards against reply_to loops in hand-edited files.
+    if depth < 32 {
+        for reply in comments
+            .iter()
+            .filter(|c| c.reply_to == Some(comment.id) && c.id != comment.id)
+        {
+            render(comments, reply, depth + 1, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: u32, reply_to: Option<u32>, body: &str) -> Comment {
+        Comment {
+            id,
+            author: "ana".to_string(),
+            date: "d".to_string(),
+            reply_to,
+            handoff_to: None,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_threads() {
+        let comments = vec![
+            comment(1, None, "Question?\n\nDetails."),
+            comment(2, None, "Other"),
+            comment(3, Some(1), "Answer"),
+            comment(4, Some(9), "Orphan"),
+        ];
+        assert_eq!(
+            render_threads(&comments),
+            "[1] ana (d)\n  Question?\n\n  Details.\n  [3] ana (d)\n    Answer\n[2] ana (d)\n  Other\n[4] ana (d)\n  Orphan\n"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! `llmd issue doctor`
+
+use anyhow::Result;
+use clap::Parser;
+use std::collections::BTreeMap;
+
+use crate::issues::{file_ops, graph, load_all_issues, load_config};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct DoctorArgs {
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: DoctorArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let issues = load_all_issues(&issues_dir)?;
+    let workflow = load_config(&issues_dir)?.workflow;
+    let mut problems = duplicate_ids(&issues_dir)?;
+    problems.extend(graph::check(&issues, &workflow));
+
+    if args.json {
+        let out: Vec<serde_json::Value> = problems
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "kind": p.kind,
+                    "issues": p.issues,
+                    "message": p.message,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        for p in &problems {
+            println!("{}: {}", p.kind, p.message);
+        }
+    }
+
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "{} problem(s) found in {} issue(s)",
+            problems.len(),
+            issues.len()
+        );
+    }
+    eprintln!("No problems found in {} issue(s).", issues.len());
+    Ok(())
+}
+
+/// Ids used by more than one file, typically after merging two branches that
+/// both created issues.
+fn duplicate_ids(issues_dir: &std::path::Path) -> Result<Vec<graph::Problem>> {
+    let mut by_id: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    for (id, path) in file_ops::issue_files(issues_dir)? {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        by_id.entry(id).or_default().push(name.into_owned());
+    }
+    Ok(by_id
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(id, names)| graph::Problem {
+            kind: "duplicate-id",
+            issues: vec![id],
+            message: format!(
+                "#{id} is used by {}; run `llmd issue renumber`",
+                names.join(", ")
+            ),
+        })
+        .collect())
+}
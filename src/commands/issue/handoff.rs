@@ -0,0 +1,81 @@
+//! `llmd issue handoff`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+
+use super::update::author;
+use crate::issues::frontmatter::parse_issue;
+use crate::issues::{file_ops, load_config, log, resolve_issue_path, write_issue};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct HandoffArgs {
+    pub id_or_slug: String,
+
+    /// Agent taking over the issue
+    #[arg(long)]
+    pub to: String,
+
+    /// Summary for the next agent: what is done, what is left, what to watch out for
+    #[arg(long)]
+    pub note: String,
+
+    /// The outgoing agent; defaults to $USER
+    #[arg(long)]
+    pub author: Option<String>,
+}
+
+pub fn run(args: HandoffArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    if args.note.trim().is_empty() {
+        anyhow::bail!("The handoff note is empty");
+    }
+
+    let _lock = file_ops::lock(&issues_dir)?;
+
+    let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
+        .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+
+    let id: u32 = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.split('-').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let mut issue = parse_issue(&content, id).context("Failed to parse issue")?;
+    let workflow = load_config(&issues_dir)?.workflow;
+
+    if workflow.is_terminal(&issue.status) {
+        anyhow::bail!("Issue #{id} is {}", issue.status);
+    }
+
+    // The outgoing agent's claim ends so the issue shows up in `ready` for
+    // the next one.
+    let before = issue.clone();
+    let now = file_ops::now_iso();
+    let from = author(args.author);
+    issue.add_comment(&from, &now, args.note.trim(), None);
+    if let Some(comment) = issue.comments.last_mut() {
+        comment.handoff_to = Some(args.to.clone());
+    }
+    issue.handover_to = Some(args.to.clone());
+    issue.assignee = Some(args.to.clone());
+    issue.lease_expires_at = None;
+    issue.updated_at = now;
+    write_issue(&issues_dir, &issue)?;
+    log::record(&issues_dir, Some(&before), &issue, &from);
+
+    eprintln!("Handed issue #{id} from {from} to {}", args.to);
+    Ok(())
+}
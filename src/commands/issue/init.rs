@@ -4,19 +4,36 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use std::fs;
 
-use crate::issues::{load_config, save_config};
+use crate::issues::models::IdScheme;
+use crate::issues::{file_ops, load_config, save_config};
 use crate::llmd_dir;
 
 #[derive(Parser)]
-pub struct InitArgs {}
+pub struct InitArgs {
+    /// How new issues get ids; on an existing tracker, switches to this scheme
+    #[arg(long, value_enum)]
+    pub ids: Option<IdScheme>,
+}
 
-pub fn run(_args: InitArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+pub fn run(args: InitArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if issues_dir.exists() {
-        let config = load_config(&issues_dir)?;
+        let _lock = file_ops::lock(&issues_dir)?;
+        let mut config = load_config(&issues_dir)?;
+        if let Some(ids) = args.ids
+            && ids != config.id_scheme
+        {
+            config.id_scheme = ids;
+            save_config(&issues_dir, &config)?;
+            let name = match ids {
+                IdScheme::Sequential => "sequential",
+                IdScheme::Hash => "hash",
+            };
+            eprintln!("New issues in .llmd/issues/ now get {name} ids.");
+            return Ok(());
+        }
         eprintln!(
             ".llmd/issues/ already exists (next_id: {}).",
             config.next_id
@@ -25,7 +42,10 @@ pub fn run(_args: InitArgs) -> Result<()> {
     }
 
     fs::create_dir_all(&issues_dir).context("Failed to create .llmd/issues/")?;
-    let config = crate::issues::Config::default();
+    let config = crate::issues::Config {
+        id_scheme: args.ids.unwrap_or_default(),
+        ..Default::default()
+    };
     save_config(&issues_dir, &config)?;
 
     eprintln!("Initialised .llmd/issues/ at {}", issues_dir.display());
@@ -28,8 +28,7 @@ pub struct ListArgs {
 }
 
 pub fn run(args: ListArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if !issues_dir.is_dir() {
@@ -38,7 +37,8 @@ pub fn run(args: ListArgs) -> Result<()> {
 
     let mut issues = load_all_issues(&issues_dir)?;
 
-    if let Some(epic_id) = args.epic {
+    if let Some(epic) = args.epic {
+        let epic_id = crate::issues::models::resolve_id(&issues, epic)?;
         issues.retain(|_, i| i.parent == Some(epic_id));
     }
     if let Some(ref status) = args.status {
@@ -63,6 +63,7 @@ pub fn run(args: ListArgs) -> Result<()> {
             .map(|i| {
                 serde_json::json!({
                     "id": i.id,
+                    "alias": i.alias,
                     "title": i.title,
                     "slug": i.slug,
                     "type": i.issue_type,
@@ -70,9 +71,11 @@ pub fn run(args: ListArgs) -> Result<()> {
                     "priority": i.priority,
                     "labels": i.labels.iter().map(|l| serde_json::json!({"name": l.name, "color": l.color})).collect::<Vec<_>>(),
                     "assignee": i.assignee,
+                    "handover_to": i.handover_to,
                     "milestone": i.milestone,
                     "parent": i.parent,
                     "dependencies": i.dependencies,
+                    "resolution": i.resolution,
                 })
             })
             .collect();
@@ -86,9 +89,18 @@ pub fn run(args: ListArgs) -> Result<()> {
                 .collect::<Vec<_>>()
                 .join(", ");
             let assignee = i.assignee.as_deref().unwrap_or("—");
+            let status = match &i.resolution {
+                Some(r) => format!("{} ({r})", i.status),
+                None => i.status.clone(),
+            };
             println!(
-                "#{} {} [{}] {} · {} · {}",
-                i.id, i.title, i.issue_type, i.status, assignee, labels
+                "{} {} [{}] {} · {} · {}",
+                i.display_id(),
+                i.title,
+                i.issue_type,
+                status,
+                assignee,
+                labels
             );
         }
     }
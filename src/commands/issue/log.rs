@@ -0,0 +1,51 @@
+//! `llmd issue log`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+
+use crate::issues::{log, resolve_issue_path};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct LogArgs {
+    pub id_or_slug: String,
+
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: LogArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
+        .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
+
+    let id: u32 = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.split('-').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let (mut events, skipped) = log::load(&issues_dir)?;
+    if skipped > 0 {
+        eprintln!("Warning: skipped {skipped} malformed line(s) in the issue log");
+    }
+    events.retain(|e| e.issue == id);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+    } else if events.is_empty() {
+        eprintln!("No recorded changes to issue #{id}.");
+    } else {
+        for e in &events {
+            println!("{} {} {}", e.ts, e.author, super::activity::describe(e));
+        }
+    }
+    Ok(())
+}
@@ -2,80 +2,56 @@
 
 use anyhow::Result;
 use clap::Parser;
-use std::fs;
 
+use crate::issues::{comments, load_all_issues};
 use crate::llmd_dir;
 
 #[derive(Parser)]
 pub struct MentionsArgs {
+    /// Only comments mentioning @HANDLE; all mentions if omitted
     #[arg(required = false)]
     pub handle: Option<String>,
 }
 
 pub fn run(args: MentionsArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if !issues_dir.is_dir() {
         anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
     }
 
-    let pattern = args
-        .handle
-        .as_ref()
-        .map(|h| format!("@{}", h))
-        .unwrap_or_else(|| "@\\w+".to_string());
-
-    let re = regex_lite::Regex::new(&pattern)
-        .unwrap_or_else(|_| regex_lite::Regex::new("@\\w+").unwrap());
-
-    let entries = fs::read_dir(&issues_dir)?;
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("md") {
-            continue;
-        }
-        let content = match fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let comments_section = content.split("## Comments").nth(1).unwrap_or("");
-        let yaml_block = comments_section
-            .split("```yaml")
-            .nth(1)
-            .and_then(|s| s.split("```").next())
-            .unwrap_or("");
-
-        for line in yaml_block.lines() {
-            if re.is_match(line) {
-                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-                let id = stem.split('-').next().unwrap_or("");
-                let body = line
-                    .split("body:")
-                    .nth(1)
-                    .and_then(|s| s.split_whitespace().next())
-                    .unwrap_or(line);
-                let author = line
-                    .split("author:")
-                    .nth(1)
-                    .and_then(|s| s.split_whitespace().next())
-                    .unwrap_or("?");
-                let date = line
-                    .split("date:")
-                    .nth(1)
-                    .and_then(|s| s.split_whitespace().next())
-                    .unwrap_or("?");
-                println!(
-                    "#{} {} — {} ({}): {}",
-                    id,
-                    stem,
-                    author.trim_matches('"'),
-                    date.trim_matches('"'),
-                    body.trim_matches('"')
-                );
-                break;
+    let handle = args.handle.as_deref().map(|h| h.trim_start_matches('@'));
+    let issues = load_all_issues(&issues_dir)?;
+    let mut sorted: Vec<_> = issues.values().collect();
+    sorted.sort_by_key(|i| i.id);
+
+    for issue in sorted {
+        let last_handoff = issue.comments.iter().rposition(|c| c.handoff_to.is_some());
+        for (n, comment) in issue.comments.iter().enumerate() {
+            // A handoff mentions the agent it hands the issue to.
+            let mut mentioned = comments::mentions(&comment.body);
+            mentioned.extend(comment.handoff_to.as_deref());
+            if mentioned.is_empty() || handle.is_some_and(|h| !mentioned.contains(&h)) {
+                continue;
             }
+            let handoff = match &comment.handoff_to {
+                Some(to) if issue.handover_to.as_ref() == Some(to) && last_handoff == Some(n) => {
+                    format!(" → handoff to {to}, pending")
+                }
+                Some(to) => format!(" → handoff to {to}"),
+                None => String::new(),
+            };
+            let first_line = comment.body.lines().next().unwrap_or_default();
+            let more = if comment.body.lines().nth(1).is_some() {
+                " …"
+            } else {
+                ""
+            };
+            println!(
+                "#{} {} [{}] — {} ({}){handoff}: {first_line}{more}",
+                issue.id, issue.slug, comment.id, comment.author, comment.date
+            );
         }
     }
     Ok(())
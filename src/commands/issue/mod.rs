@@ -1,10 +1,20 @@
 //! `llmd issue` — issue tracker subcommands.
 
+mod activity;
+mod claim;
+mod close;
+mod comment;
+mod comments;
+mod doctor;
+mod handoff;
 mod init;
 mod list;
+mod log;
 mod mentions;
 mod new;
 mod ready;
+mod release;
+mod renumber;
 mod show;
 mod tree;
 mod update;
@@ -30,12 +40,32 @@ pub enum IssueCommand {
     Show(show::ShowArgs),
     /// Update an issue
     Update(update::UpdateArgs),
+    /// Close an issue with a resolution: done, wontfix or duplicate:<id>
+    Close(close::CloseArgs),
     /// List open issues with no unresolved dependencies
     Ready(ready::ReadyArgs),
+    /// Claim an issue for an agent: assign it, start it and lease it for a while
+    Claim(claim::ClaimArgs),
+    /// Give up a claim so the issue shows up in `ready` again
+    Release(release::ReleaseArgs),
+    /// Hand an issue to another agent with a summary of where things stand
+    Handoff(handoff::HandoffArgs),
+    /// Add a comment (from stdin if no text is given), optionally as a reply
+    Comment(comment::CommentArgs),
+    /// Show an issue's comments as threads
+    Comments(comments::CommentsArgs),
+    /// Show the recorded changes to an issue
+    Log(log::LogArgs),
+    /// Show recent changes across all issues
+    Activity(activity::ActivityArgs),
     /// Print epic hierarchy
     Tree(tree::TreeArgs),
     /// List issues with @mentions
     Mentions(mentions::MentionsArgs),
+    /// Report dependency cycles, dangling ids and parent/epic mismatches
+    Doctor(doctor::DoctorArgs),
+    /// Give issues that share an id or alias after a git merge new ones
+    Renumber(renumber::RenumberArgs),
 }
 
 pub fn run(args: IssueArgs) -> Result<()> {
@@ -45,8 +75,18 @@ pub fn run(args: IssueArgs) -> Result<()> {
         IssueCommand::List(a) => list::run(a),
         IssueCommand::Show(a) => show::run(a),
         IssueCommand::Update(a) => update::run(a),
+        IssueCommand::Close(a) => close::run(a),
         IssueCommand::Ready(a) => ready::run(a),
+        IssueCommand::Claim(a) => claim::run(a),
+        IssueCommand::Release(a) => release::run(a),
+        IssueCommand::Handoff(a) => handoff::run(a),
+        IssueCommand::Comment(a) => comment::run(a),
+        IssueCommand::Comments(a) => comments::run(a),
+        IssueCommand::Log(a) => log::run(a),
+        IssueCommand::Activity(a) => activity::run(a),
         IssueCommand::Tree(a) => tree::run(a),
         IssueCommand::Mentions(a) => mentions::run(a),
+        IssueCommand::Doctor(a) => doctor::run(a),
+        IssueCommand::Renumber(a) => renumber::run(a),
     }
 }
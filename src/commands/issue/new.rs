@@ -1,10 +1,12 @@
 //! `llmd issue new`
 
-use crate::issues::models::{Issue, Label};
-use crate::issues::{file_ops, load_config, save_config, write_issue};
+use super::update::author;
+use crate::issues::models::{self, IdScheme, Issue, Label};
+use crate::issues::{file_ops, graph, load_all_issues, load_config, log, save_config, write_issue};
 use crate::llmd_dir;
 use anyhow::{Context, Result};
 use clap::Parser;
+use serde_yaml_ng::Mapping;
 
 fn slugify(s: &str) -> String {
     s.to_lowercase()
@@ -26,11 +28,13 @@ fn slugify(s: &str) -> String {
 pub struct NewArgs {
     pub title: String,
 
-    #[arg(long, default_value = "task")]
-    pub r#type: String,
+    /// Defaults to `task`, or the first type in the workflow
+    #[arg(long)]
+    pub r#type: Option<String>,
 
-    #[arg(long, default_value = "medium")]
-    pub priority: String,
+    /// Defaults to `medium`, or the middle priority in the workflow
+    #[arg(long)]
+    pub priority: Option<String>,
 
     #[arg(long, value_delimiter = ',')]
     pub labels: Vec<String>,
@@ -55,21 +59,44 @@ pub struct NewArgs {
 }
 
 pub fn run(args: NewArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if !issues_dir.is_dir() {
         anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
     }
 
+    // Allocate the id and write the files under the lock so parallel agents
+    // get distinct ids.
+    let _lock = file_ops::lock(&issues_dir)?;
+
     let mut config = load_config(&issues_dir)?;
-    let id = config.next_id;
-    config.next_id += 1;
-    save_config(&issues_dir, &config)?;
+    let mut issues = load_all_issues(&issues_dir)?;
+    let now = file_ops::now_iso();
+    let seed = format!("{}\0{now}\0{}", args.title, std::process::id());
+    let id = config.allocate_id(&issues, &seed);
+    let alias = (config.id_scheme == IdScheme::Hash).then(|| models::next_alias(&issues));
+    let parent = args
+        .parent
+        .map(|p| models::resolve_id(&issues, p))
+        .transpose()?;
+    let dependencies = args
+        .dep
+        .iter()
+        .map(|&d| models::resolve_id(&issues, d))
+        .collect::<Result<Vec<_>>>()?;
+
+    let workflow = &config.workflow;
+    let issue_type = args
+        .r#type
+        .unwrap_or_else(|| workflow.default_type().to_string());
+    let priority = args
+        .priority
+        .unwrap_or_else(|| workflow.default_priority().to_string());
+    workflow.check_type(&issue_type)?;
+    workflow.check_priority(&priority)?;
 
     let slug = slugify(&args.title);
-    let now = file_ops::now_iso();
 
     let labels: Vec<Label> = args
         .labels
@@ -85,35 +112,59 @@ pub fn run(args: NewArgs) -> Result<()> {
 
     let issue = Issue {
         id,
+        alias,
         title: args.title.clone(),
         slug: slug.clone(),
-        issue_type: args.r#type,
-        status: "open".to_string(),
-        priority: args.priority,
+        issue_type,
+        status: workflow.initial_status().to_string(),
+        priority,
         labels,
         assignee: args.assignee,
+        handover_to: None,
         milestone: args.milestone,
-        parent: args.parent,
-        dependencies: args.dep,
+        parent,
+        dependencies,
         epic_children: vec![],
         points: args.points,
         due: args.due,
         created_at: now.clone(),
         updated_at: now,
+        resolution: None,
+        closed_at: None,
+        lease_expires_at: None,
         body: String::new(),
+        comments: Vec::new(),
+        frontmatter: Mapping::new(),
     };
 
+    issues.insert(id, issue.clone());
+    graph::ensure_acyclic(&issues, id)?;
+
+    // Hash ids leave config.json alone, so branches do not conflict on it.
+    if config.id_scheme == IdScheme::Sequential {
+        save_config(&issues_dir, &config)?;
+    }
     write_issue(&issues_dir, &issue)?;
+    let who = author(None);
+    log::record(&issues_dir, None, &issue, &who);
 
-    if let Some(parent_id) = args.parent {
-        add_child_to_epic(&issues_dir, parent_id, id)?;
+    if let Some(parent_id) = parent {
+        add_child_to_epic(&issues_dir, parent_id, id, &who)?;
     }
 
-    eprintln!("Created issue #{} ({})", id, slug);
+    match alias {
+        Some(alias) => eprintln!("Created issue #{id}, alias {alias} ({slug})"),
+        None => eprintln!("Created issue #{} ({})", id, slug),
+    }
     Ok(())
 }
 
-fn add_child_to_epic(issues_dir: &std::path::Path, epic_id: u32, child_id: u32) -> Result<()> {
+fn add_child_to_epic(
+    issues_dir: &std::path::Path,
+    epic_id: u32,
+    child_id: u32,
+    who: &str,
+) -> Result<()> {
     let path = match crate::issues::resolve_issue_path(issues_dir, &epic_id.to_string()) {
         Some(p) => p,
         None => return Ok(()),
@@ -124,10 +175,12 @@ fn add_child_to_epic(issues_dir: &std::path::Path, epic_id: u32, child_id: u32)
         return Ok(());
     };
     if !issue.epic_children.contains(&child_id) {
+        let before = issue.clone();
         issue.epic_children.push(child_id);
         issue.epic_children.sort();
         issue.updated_at = file_ops::now_iso();
         write_issue(issues_dir, &issue)?;
+        log::record(issues_dir, Some(&before), &issue, who);
     }
     Ok(())
 }
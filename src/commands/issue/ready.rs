@@ -3,7 +3,7 @@
 use anyhow::Result;
 use clap::Parser;
 
-use crate::issues::{load_all_issues, ready_tasks};
+use crate::issues::{load_all_issues, load_config, ready_tasks};
 use crate::llmd_dir;
 
 #[derive(Parser)]
@@ -19,11 +19,14 @@ pub struct ReadyArgs {
 
     #[arg(long)]
     pub assignee: Option<String>,
+
+    /// Issues for this agent: handoffs to it first, without handoffs to others
+    #[arg(long)]
+    pub agent: Option<String>,
 }
 
 pub fn run(args: ReadyArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if !issues_dir.is_dir() {
@@ -31,12 +34,16 @@ pub fn run(args: ReadyArgs) -> Result<()> {
     }
 
     let issues = load_all_issues(&issues_dir)?;
-    let ready = ready_tasks(&issues, true);
+    let workflow = load_config(&issues_dir)?.workflow;
+    let ready = ready_tasks(&issues, &workflow, true);
 
-    let filtered: Vec<_> = ready
+    let mut filtered: Vec<_> = ready
         .iter()
         .filter(|i| {
-            args.type_filter.as_ref().is_none_or(|t| i.issue_type == *t)
+            args.agent
+                .as_ref()
+                .is_none_or(|a| i.handover_to.as_ref().is_none_or(|to| to == a))
+                && args.type_filter.as_ref().is_none_or(|t| i.issue_type == *t)
                 && args
                     .milestone
                     .as_ref()
@@ -47,6 +54,10 @@ pub fn run(args: ReadyArgs) -> Result<()> {
                     .is_none_or(|a| i.assignee.as_deref() == Some(a.as_str()))
         })
         .collect();
+    if args.agent.is_some() {
+        // Stable sort: handoffs first, each group still in priority order.
+        filtered.sort_by_key(|i| i.handover_to.is_none());
+    }
 
     if args.json {
         let out: Vec<serde_json::Value> = filtered
@@ -54,12 +65,14 @@ pub fn run(args: ReadyArgs) -> Result<()> {
             .map(|i| {
                 serde_json::json!({
                     "id": i.id,
+                    "alias": i.alias,
                     "title": i.title,
                     "slug": i.slug,
                     "type": i.issue_type,
                     "status": i.status,
                     "priority": i.priority,
                     "assignee": i.assignee,
+                    "handover_to": i.handover_to,
                     "milestone": i.milestone,
                 })
             })
@@ -68,7 +81,18 @@ pub fn run(args: ReadyArgs) -> Result<()> {
     } else {
         for i in filtered {
             let assignee = i.assignee.as_deref().unwrap_or("—");
-            println!("#{} {} [{}] · {}", i.id, i.title, i.issue_type, assignee);
+            let handoff = i
+                .handover_to
+                .as_ref()
+                .map(|to| format!(" · handed to {to}"))
+                .unwrap_or_default();
+            println!(
+                "{} {} [{}] · {}{handoff}",
+                i.display_id(),
+                i.title,
+                i.issue_type,
+                assignee
+            );
         }
     }
     Ok(())
@@ -0,0 +1,76 @@
+//! `llmd issue release`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+
+use super::update::author;
+use crate::issues::frontmatter::parse_issue;
+use crate::issues::{file_ops, load_config, log, resolve_issue_path, write_issue};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct ReleaseArgs {
+    pub id_or_slug: String,
+
+    /// Only release if this agent holds the claim
+    #[arg(long)]
+    pub agent: Option<String>,
+}
+
+pub fn run(args: ReleaseArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let _lock = file_ops::lock(&issues_dir)?;
+
+    let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
+        .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+
+    let id: u32 = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.split('-').next())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let mut issue = parse_issue(&content, id).context("Failed to parse issue")?;
+
+    if issue.lease_expires_at.is_none() {
+        anyhow::bail!("Issue #{id} is not claimed");
+    }
+    if let Some(agent) = &args.agent
+        && issue.has_live_lease()
+        && issue.assignee.as_deref() != Some(agent.as_str())
+    {
+        anyhow::bail!(
+            "Issue #{id} is claimed by {}, not {agent}",
+            issue.assignee.as_deref().unwrap_or("another agent")
+        );
+    }
+
+    let before = issue.clone();
+    // Hand the issue back: unassigned and, if the claim started it, open again.
+    let workflow = load_config(&issues_dir)?.workflow;
+    let initial = workflow.initial_status();
+    if workflow.active_status() == Some(issue.status.as_str())
+        && workflow.check_transition(&issue.status, initial).is_ok()
+    {
+        issue.status = initial.to_string();
+    }
+    issue.assignee = None;
+    issue.lease_expires_at = None;
+    issue.updated_at = file_ops::now_iso();
+    write_issue(&issues_dir, &issue)?;
+    log::record(&issues_dir, Some(&before), &issue, &author(args.agent));
+
+    eprintln!("Released issue #{id}");
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! `llmd issue renumber`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+
+use super::update::author;
+use crate::issues::frontmatter::parse_issue;
+use crate::issues::renumber::{self, Move};
+use crate::issues::{file_ops, load_config, log, save_config, write_issue};
+use crate::llmd_dir;
+
+#[derive(Parser)]
+pub struct RenumberArgs {
+    /// Print what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn run(args: RenumberArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let issues_dir = llmd_dir::issues_path(&llmd);
+
+    if !issues_dir.is_dir() {
+        anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
+    }
+
+    let _lock = file_ops::lock(&issues_dir)?;
+    let mut config = load_config(&issues_dir)?;
+    let next_id = config.next_id;
+
+    let mut files = Vec::new();
+    for (id, path) in file_ops::issue_files(&issues_dir)? {
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+        match parse_issue(&content, id) {
+            Some(issue) => files.push((path, issue)),
+            None => eprintln!("Warning: skipping {}: no frontmatter", path.display()),
+        }
+    }
+
+    let mut plan = renumber::plan(&mut config, files);
+    for m in &plan.moves {
+        println!(
+            "#{} {} → #{} ({})",
+            m.old,
+            m.path.file_name().unwrap_or_default().to_string_lossy(),
+            m.new,
+            plan.issues[&m.new].title
+        );
+    }
+    for m in &plan.moves {
+        let referrers = plan.referrers(m.old);
+        if !referrers.is_empty() {
+            let names: Vec<String> = referrers.iter().map(|r| format!("#{r}")).collect();
+            eprintln!(
+                "Check {}: they refer to #{}, which stays \"{}\"; point them at #{} if they meant \"{}\"",
+                names.join(", "),
+                m.old,
+                plan.issues[&m.old].title,
+                m.new,
+                plan.issues[&m.new].title
+            );
+        }
+    }
+    for (id, old, new) in &plan.aliases {
+        println!("#{id} alias {old} → {new}");
+    }
+    if config.next_id != next_id {
+        println!("next_id {next_id} → {}", config.next_id);
+    }
+
+    if plan.dirty.is_empty() && config.next_id == next_id {
+        eprintln!(
+            "No duplicate ids or aliases in {} issue(s).",
+            plan.issues.len()
+        );
+        return Ok(());
+    }
+    if args.dry_run {
+        eprintln!("Dry run: nothing written.");
+        return Ok(());
+    }
+
+    let now = file_ops::now_iso();
+    let who = author(None);
+    for id in &plan.dirty {
+        let issue = plan.issues.get_mut(id).expect("dirty ids are in the map");
+        issue.updated_at = now.clone();
+        write_issue(&issues_dir, issue)?;
+        log::record(&issues_dir, plan.before.get(id), issue, &who);
+    }
+    for Move { path, .. } in &plan.moves {
+        fs::remove_file(path).with_context(|| format!("Cannot remove {}", path.display()))?;
+    }
+    if config.next_id != next_id {
+        save_config(&issues_dir, &config)?;
+    }
+
+    eprintln!(
+        "Renumbered {} issue(s) and reassigned {} alias(es).",
+        plan.moves.len(),
+        plan.aliases.len()
+    );
+    Ok(())
+}
@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use std::fs;
 
+use crate::issues::models::Resolution;
 use crate::issues::{load_all_issues, resolve_issue_path};
 use crate::llmd_dir;
 
@@ -16,8 +17,7 @@ pub struct ShowArgs {
 }
 
 pub fn run(args: ShowArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if !issues_dir.is_dir() {
@@ -39,8 +39,15 @@ pub fn run(args: ShowArgs) -> Result<()> {
             .and_then(|s| s.parse().ok())
             .unwrap_or(0);
         if let Some(issue) = issues.get(&id) {
+            let mut duplicates: Vec<u32> = issues
+                .values()
+                .filter(|i| i.resolution() == Some(Resolution::Duplicate(id)))
+                .map(|i| i.id)
+                .collect();
+            duplicates.sort();
             let out = serde_json::json!({
                 "id": issue.id,
+                "alias": issue.alias,
                 "title": issue.title,
                 "slug": issue.slug,
                 "type": issue.issue_type,
@@ -48,6 +55,7 @@ pub fn run(args: ShowArgs) -> Result<()> {
                 "priority": issue.priority,
                 "labels": issue.labels,
                 "assignee": issue.assignee,
+                "handover_to": issue.handover_to,
                 "milestone": issue.milestone,
                 "parent": issue.parent,
                 "dependencies": issue.dependencies,
@@ -56,6 +64,11 @@ pub fn run(args: ShowArgs) -> Result<()> {
                 "due": issue.due,
                 "created_at": issue.created_at,
                 "updated_at": issue.updated_at,
+                "resolution": issue.resolution,
+                "closed_at": issue.closed_at,
+                "lease_expires_at": issue.lease_expires_at,
+                "duplicates": duplicates,
+                "comments": issue.comments,
             });
             println!("{}", serde_json::to_string_pretty(&out)?);
         }
@@ -1,9 +1,10 @@
 //! `llmd issue tree`
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::Parser;
 
-use crate::issues::{epic_tree, load_all_issues, resolve_issue_path};
+use crate::issues::models::resolve_id;
+use crate::issues::{epic_tree, load_all_issues};
 use crate::llmd_dir;
 
 #[derive(Parser)]
@@ -12,19 +13,15 @@ pub struct TreeArgs {
 }
 
 pub fn run(args: TreeArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if !issues_dir.is_dir() {
         anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
     }
 
-    let _path = resolve_issue_path(&issues_dir, &args.id.to_string())
-        .with_context(|| format!("Issue #{} not found", args.id))?;
-
     let issues = load_all_issues(&issues_dir)?;
-    let tree = epic_tree(&issues, args.id);
+    let tree = epic_tree(&issues, resolve_id(&issues, args.id)?);
 
     for (id, depth) in tree {
         let indent = "  ".repeat(depth);
@@ -5,8 +5,10 @@ use clap::Parser;
 use std::fs;
 
 use crate::issues::frontmatter::parse_issue;
-use crate::issues::models::Label;
-use crate::issues::{file_ops, resolve_issue_path, write_issue};
+use crate::issues::models::{self, Label};
+use crate::issues::{
+    file_ops, graph, load_all_issues, load_config, log, resolve_issue_path, write_issue,
+};
 use crate::llmd_dir;
 
 #[derive(Parser)]
@@ -45,17 +47,22 @@ pub struct UpdateArgs {
 
     #[arg(long)]
     pub author: Option<String>,
+
+    /// Fail if the issue's updated_at is no longer this, i.e. it changed since you read it
+    #[arg(long, value_name = "TIMESTAMP")]
+    pub expect_updated_at: Option<String>,
 }
 
 pub fn run(args: UpdateArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let issues_dir = llmd_dir::issues_path(&llmd);
 
     if !issues_dir.is_dir() {
         anyhow::bail!(".llmd/issues/ not found. Run `llmd issue init` first.");
     }
 
+    let _lock = file_ops::lock(&issues_dir)?;
+
     let path = resolve_issue_path(&issues_dir, &args.id_or_slug)
         .with_context(|| format!("Issue \"{}\" not found", args.id_or_slug))?;
 
@@ -70,11 +77,46 @@ pub fn run(args: UpdateArgs) -> Result<()> {
         .unwrap_or(0);
 
     let mut issue = parse_issue(&content, id).context("Failed to parse issue")?;
+    if let Some(expected) = &args.expect_updated_at
+        && *expected != issue.updated_at
+    {
+        anyhow::bail!(
+            "Issue #{id} changed since you read it (updated_at is now {}, expected {expected}). Re-read it and try again.",
+            issue.updated_at
+        );
+    }
+    let read_at = issue.updated_at.clone();
+    let before = issue.clone();
+    let who = author(args.author);
+    let workflow = load_config(&issues_dir)?.workflow;
+    // Dependencies and parents may be given by alias; store the ids.
+    let mut issues = if args.add_dep.is_some() || args.parent.is_some() {
+        load_all_issues(&issues_dir)?
+    } else {
+        Default::default()
+    };
+    let add_dep = args
+        .add_dep
+        .map(|d| models::resolve_id(&issues, d))
+        .transpose()?;
+    let parent = args
+        .parent
+        .map(|p| models::resolve_id(&issues, p))
+        .transpose()?;
 
     if let Some(s) = args.status {
+        workflow.check_transition(&issue.status, &s)?;
+        // Reopening clears the resolution; closing without one counts as done.
+        if workflow.is_terminal(&s) {
+            issue.closed_at.get_or_insert_with(file_ops::now_iso);
+        } else {
+            issue.resolution = None;
+            issue.closed_at = None;
+        }
         issue.status = s;
     }
     if let Some(p) = args.priority {
+        workflow.check_priority(&p)?;
         issue.priority = p;
     }
     if let Some(a) = args.assignee {
@@ -89,7 +131,7 @@ pub fn run(args: UpdateArgs) -> Result<()> {
     if let Some(d) = args.due {
         issue.due = Some(d);
     }
-    if let Some(dep) = args.add_dep
+    if let Some(dep) = add_dep
         && !issue.dependencies.contains(&dep)
     {
         issue.dependencies.push(dep);
@@ -104,53 +146,31 @@ pub fn run(args: UpdateArgs) -> Result<()> {
             issue.labels.push(Label { name, color });
         }
     }
-    if let Some(p) = args.parent {
+    if let Some(p) = parent {
         issue.parent = Some(p);
     }
     if let Some(ref comment_text) = args.add_comment {
-        let author = args
-            .author
-            .unwrap_or_else(|| std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()));
         let date = file_ops::now_iso();
-        append_comment(&mut issue.body, &author, &date, comment_text);
+        issue.add_comment(&who, &date, comment_text, None);
+    }
+
+    if add_dep.is_some() || parent.is_some() {
+        issues.insert(id, issue.clone());
+        graph::ensure_acyclic(&issues, id)?;
     }
 
+    // The lock only keeps out other llmd processes; an editor or a git
+    // checkout may still have rewritten the file.
+    file_ops::ensure_unchanged(&issues_dir, id, &read_at)?;
     issue.updated_at = file_ops::now_iso();
     write_issue(&issues_dir, &issue)?;
+    log::record(&issues_dir, Some(&before), &issue, &who);
 
     eprintln!("Updated issue #{}", id);
     Ok(())
 }
 
-fn append_comment(body: &mut String, author: &str, date: &str, text: &str) {
-    let entry = format!(
-        "- author: \"{}\"\n  date: \"{}\"\n  body: \"{}\"\n",
-        author.replace('\\', "\\\\").replace('"', "\\\""),
-        date,
-        text.replace('\\', "\\\\").replace('"', "\\\"")
-    );
-
-    if !body.contains("## Comments") {
-        if !body.trim().is_empty() {
-            body.push_str("\n\n");
-        }
-        body.push_str("## Comments\n\n```yaml\n");
-        body.push_str(&entry);
-        body.push_str("```\n");
-        return;
-    }
-
-    if let Some(idx) = body.find("```yaml") {
-        let after_yaml = idx + 7;
-        if let Some(close) = body[after_yaml..].find("\n```") {
-            let insert_at = after_yaml + close;
-            body.insert_str(insert_at, &entry);
-            return;
-        }
-    }
-    if let Some(idx) = body.rfind("```") {
-        body.insert_str(idx, &entry);
-    } else {
-        body.push_str(&entry);
-    }
+/// The comment author: `--author`, else `$USER`, else `unknown`.
+pub(super) fn author(arg: Option<String>) -> String {
+    arg.unwrap_or_else(|| std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()))
 }
@@ -1,9 +1,15 @@
 pub mod bootstrap;
 pub mod build;
 pub mod compose;
+pub mod config;
 pub mod index;
 pub mod init;
 pub mod issue;
+pub mod pack;
 pub mod read;
 pub mod search;
 pub mod serve;
+pub mod snapshot;
+pub mod stats;
+pub mod usage;
+pub mod watch;
@@ -0,0 +1,234 @@
+//! `llmd pack` — manage external knowledge packs.
+//!
+//! A pack is a read-only `.llmd/` from outside the project, declared under
+//! `packs` in `.llmd/llmd.json`. Local packs are read in place; git packs are
+//! vendored into `.llmd/packs/<name>/` by `add` and `update` only, so normal
+//! commands never touch the network. Vendored files are marked read-only.
+//! Pack sections appear in `index`, `search` and `compose` as
+//! `pack:<name> > file > Heading`.
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{config, llmd_dir};
+
+#[derive(Parser)]
+pub struct PackArgs {
+    #[command(subcommand)]
+    pub command: PackCommand,
+}
+
+#[derive(Subcommand)]
+pub enum PackCommand {
+    /// Declare a pack: vendor a git repository, or reference a local directory with --path
+    Add {
+        /// Pack name, used in labels as `pack:<name>`
+        name: String,
+        /// Git URL to vendor into .llmd/packs/<name>/
+        #[arg(required_unless_present = "path", conflicts_with = "path")]
+        url: Option<String>,
+        /// Local directory to read in place (absolute or relative to the project root)
+        #[arg(long)]
+        path: Option<String>,
+        /// Branch, tag or commit to check out
+        #[arg(long, requires = "url")]
+        rev: Option<String>,
+    },
+    /// Re-vendor git packs (all of them, or the named one)
+    Update {
+        /// Pack to update
+        name: Option<String>,
+    },
+    /// List declared packs
+    List,
+}
+
+pub fn run(args: PackArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+
+    match args.command {
+        PackCommand::Add {
+            name,
+            url,
+            path,
+            rev,
+        } => {
+            let mut entry = match &url {
+                Some(url) => json!({ "git": url }),
+                None => json!({ "path": path }),
+            };
+            if let Some(rev) = &rev {
+                entry["rev"] = json!(rev);
+            }
+            if llmd_dir::config(&llmd)?.packs.contains_key(&name) {
+                bail!(
+                    "Pack \"{name}\" already exists. Use `llmd pack update {name}` to refresh it."
+                );
+            }
+            // Check the name before it is used as a directory.
+            config::from_value(json!({ "packs": { &name: &entry } }))?;
+
+            match &url {
+                Some(url) => vendor(&llmd, &name, url, rev.as_deref())?,
+                None => {
+                    let project = llmd.parent().unwrap_or(&llmd);
+                    let dir = project.join(path.as_deref().unwrap_or_default());
+                    if !dir.is_dir() {
+                        bail!("{} is not a directory.", dir.display());
+                    }
+                    eprintln!("Added local pack \"{name}\" ({}).", dir.display());
+                }
+            }
+            config::update(&llmd, |file| {
+                let root = file
+                    .as_object_mut()
+                    .context("llmd.json must contain a JSON object")?;
+                let packs = root.entry("packs").or_insert_with(|| json!({}));
+                packs
+                    .as_object_mut()
+                    .context("packs must be an object")?
+                    .insert(name.clone(), entry);
+                Ok(())
+            })?;
+        }
+        PackCommand::Update { name } => {
+            let packs = llmd_dir::config(&llmd)?.packs;
+            if let Some(name) = &name
+                && !packs.contains_key(name)
+            {
+                bail!("No pack named \"{name}\". Run `llmd pack list` to see declared packs.");
+            }
+            let mut updated = 0;
+            for (pack_name, pack) in &packs {
+                if name.as_ref().is_some_and(|n| n != pack_name) {
+                    continue;
+                }
+                match &pack.git {
+                    Some(url) => {
+                        vendor(&llmd, pack_name, url, pack.rev.as_deref())?;
+                        updated += 1;
+                    }
+                    None => eprintln!("Skipping \"{pack_name}\": local packs are read in place."),
+                }
+            }
+            if updated == 0 && name.is_none() {
+                eprintln!("No git packs to update.");
+            }
+        }
+        PackCommand::List => {
+            let packs = llmd_dir::config(&llmd)?.packs;
+            if packs.is_empty() {
+                eprintln!("No packs declared. Add one with `llmd pack add <name> <git-url>`.");
+            }
+            for (name, pack) in &packs {
+                let source = match (&pack.git, &pack.path) {
+                    (Some(url), _) => match &pack.rev {
+                        Some(rev) => format!("{url} @ {rev}"),
+                        None => url.clone(),
+                    },
+                    (None, Some(path)) => path.clone(),
+                    (None, None) => String::new(),
+                };
+                let status = match llmd_dir::pack_dir(&llmd, name) {
+                    Ok(dir) => format!("{} files", llmd_dir::list_all_files(&dir).len()),
+                    Err(_) => "missing".to_string(),
+                };
+                println!("pack:{name}  {source}  ({status})");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Clones `url` and replaces `.llmd/packs/<name>/` with its `.llmd/` directory
+/// (or the whole repository if it has none), without git metadata.
+fn vendor(llmd: &Path, name: &str, url: &str, rev: Option<&str>) -> Result<()> {
+    let packs = llmd_dir::packs_path(llmd);
+    fs::create_dir_all(&packs).with_context(|| format!("Cannot create {}", packs.display()))?;
+    let tmp = packs.join(format!(".{name}.tmp"));
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp).with_context(|| format!("Cannot remove {}", tmp.display()))?;
+    }
+
+    eprintln!("Cloning {url}…");
+    let mut clone = Command::new("git");
+    clone.arg("clone").arg("--quiet");
+    if rev.is_none() {
+        clone.args(["--depth", "1"]);
+    }
+    git(clone.arg(url).arg(&tmp), "clone")?;
+    if let Some(rev) = rev {
+        git(
+            Command::new("git")
+                .arg("-C")
+                .arg(&tmp)
+                .args(["checkout", "--quiet", rev]),
+            "checkout",
+        )?;
+    }
+    let commit = git(
+        Command::new("git")
+            .arg("-C")
+            .arg(&tmp)
+            .args(["rev-parse", "--short", "HEAD"]),
+        "rev-parse",
+    )?;
+
+    let source: PathBuf = if tmp.join(".llmd").is_dir() {
+        tmp.join(".llmd")
+    } else {
+        fs::remove_dir_all(tmp.join(".git")).context("Cannot remove cloned .git/")?;
+        tmp.clone()
+    };
+    let dest = packs.join(name);
+    if dest.exists() {
+        fs::remove_dir_all(&dest).with_context(|| format!("Cannot remove {}", dest.display()))?;
+    }
+    fs::rename(&source, &dest)
+        .with_context(|| format!("Cannot move pack into {}", dest.display()))?;
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp).ok();
+    }
+    make_read_only(&dest)?;
+
+    eprintln!(
+        "Vendored {url} @ {} into {}",
+        commit.trim(),
+        dest.strip_prefix(llmd.parent().unwrap_or(llmd))
+            .unwrap_or(&dest)
+            .display()
+    );
+    Ok(())
+}
+
+/// Runs a git command, returning its stdout or an error with its stderr.
+fn git(cmd: &mut Command, what: &str) -> Result<String> {
+    let output = cmd
+        .output()
+        .context("Failed to run `git` — is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "`git {what}` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Marks every file under `dir` read-only, so pack content is edited upstream
+/// rather than in the vendored copy.
+fn make_read_only(dir: &Path) -> Result<()> {
+    for entry in walkdir::WalkDir::new(dir).into_iter().flatten() {
+        if entry.file_type().is_file() {
+            let mut perms = entry.metadata()?.permissions();
+            perms.set_readonly(true);
+            fs::set_permissions(entry.path(), perms)
+                .with_context(|| format!("Cannot mark {} read-only", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
@@ -1,19 +1,28 @@
 //! `llmd read` — read a file or section from .llmd/.
 //!
 //! Supports reading the full file, a specific heading section, a line range,
-//! or a grep-filtered view. Optionally prints a token count estimate first.
+//! or a grep-filtered view. Optionally prints a token count first.
+//! Frontmatter is stripped and `{{#include …}}` directives are resolved unless
+//! `--raw` is given.
+//!
+//! `--since <git-ref|snapshot>` prints only the sections that changed since
+//! then (see `since`), or with `--diff` a unified diff.
 
 use anyhow::{Context, Result, bail};
 use clap::Parser;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::{llmd_dir, markdown};
+use crate::commands::compose;
+use crate::since::{self, Baseline};
+use crate::tokenizer::Tokenizer;
+use crate::{diff, llmd_dir, markdown, topic, usage};
 
 #[derive(Parser)]
 pub struct ReadArgs {
     /// File or topic name to read. Use "catme" to read catme.md.
     /// Relative paths are resolved inside .llmd/; omit the .md extension.
+    /// Prefix with `<layer>:` to read from a specific enclosing .llmd/.
     pub file: String,
 
     /// Extract only the section under this heading (case-insensitive substring match)
@@ -28,21 +37,40 @@ pub struct ReadArgs {
     #[arg(long, short, value_name = "START:END")]
     pub lines: Option<String>,
 
-    /// Print an estimated token count before the content
+    /// Print the token count before the content (see `tokenizer` in llmd.json)
     #[arg(long, short = 'T')]
     pub tokens: bool,
+
+    /// Print the file as stored: keep frontmatter and don't resolve `{{#include}}` directives
+    #[arg(long)]
+    pub raw: bool,
+
+    /// Print only the sections changed since this git revision or snapshot id
+    #[arg(long, value_name = "REF", conflicts_with_all = ["section", "lines", "grep", "raw"])]
+    pub since: Option<String>,
+
+    /// With --since, print a unified diff instead of the changed sections
+    #[arg(long, requires = "since")]
+    pub diff: bool,
 }
 
 pub fn run(args: ReadArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
 
     let path = resolve_file(&llmd, &args.file)?;
-    let content =
-        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let content = if args.raw {
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?
+    } else {
+        topic::load(&llmd, &path)?.body
+    };
 
     let mut output = content.clone();
 
+    if let Some(reference) = &args.since {
+        let baseline = Baseline::resolve(&llmd, reference)?;
+        output = changes_since(&llmd, &path, &content, &baseline, args.diff);
+    }
+
     if let Some(section) = &args.section {
         output = markdown::extract_section(&output, section)
             .with_context(|| format!("Section \"{section}\" not found in {}", path.display()))?;
@@ -58,41 +86,89 @@ pub fn run(args: ReadArgs) -> Result<()> {
     }
 
     if args.tokens {
-        let count = markdown::estimate_tokens(&output);
-        eprintln!("~{count} tokens");
+        let tokenizer = Tokenizer::for_project(&llmd)?;
+        eprintln!("{}", tokenizer.describe(tokenizer.count(&output)));
     }
 
     print!("{output}");
-    if !output.ends_with('\n') {
+    if !output.is_empty() && !output.ends_with('\n') {
         println!();
     }
 
+    let mut label = compose::file_label(&llmd, &path);
+    if args.section.is_some()
+        && let Some((_, heading)) = markdown::list_headings(&output).into_iter().next()
+    {
+        label = format!("{label} > {heading}");
+    }
+    usage::record(&llmd, "read", vec![label], &output);
+
     Ok(())
 }
 
-/// Resolves a user-supplied file name to an absolute path inside .llmd/.
-fn resolve_file(llmd: &std::path::Path, name: &str) -> Result<PathBuf> {
-    let name = if name == "catme" { "catme.md" } else { name };
-
-    let direct = llmd.join(name);
-    if direct.exists() {
-        return Ok(direct);
+/// The parts of `content` changed since `baseline`, or a unified diff. A
+/// summary goes to stderr.
+fn changes_since(
+    llmd: &Path,
+    path: &Path,
+    content: &str,
+    baseline: &Baseline,
+    as_diff: bool,
+) -> String {
+    let label = compose::file_label(llmd, path);
+    let Some(old) = baseline.body(llmd, path) else {
+        eprintln!(
+            "{label} did not exist at {}; showing all of it.",
+            baseline.name()
+        );
+        return content.to_string();
+    };
+    if as_diff {
+        let patch = diff::unified(
+            &old,
+            content,
+            &format!("{label}@{}", baseline.name()),
+            &label,
+            3,
+        );
+        if patch.is_empty() {
+            eprintln!("No changes to {label} since {}.", baseline.name());
+        }
+        return patch;
     }
 
-    let with_ext = llmd.join(format!("{name}.md"));
-    if with_ext.exists() {
-        return Ok(with_ext);
+    let (changed, removed) = since::changed_parts(&old, content);
+    if changed.is_empty() && removed.is_empty() {
+        eprintln!("No changes to {label} since {}.", baseline.name());
+        return String::new();
     }
-
-    let in_imported = llmd.join("imported").join(name);
-    if in_imported.exists() {
-        return Ok(in_imported);
+    let mut summary = format!(
+        "{} section(s) of {label} changed since {}",
+        changed.len(),
+        baseline.name()
+    );
+    if !removed.is_empty() {
+        summary.push_str(&format!("; removed: {}", removed.join(", ")));
     }
+    eprintln!("{summary}");
+    changed
+        .iter()
+        .map(|part| part.text.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+        + "\n"
+}
 
-    bail!(
-        "File \"{name}\" not found in .llmd/. \
-         Run `llmd search {name}` to find it, or `llmd read catme` to browse available docs."
-    )
+/// Resolves a user-supplied file name to an absolute path inside .llmd/ (or
+/// an enclosing workspace layer, see `llmd_dir::find_file`).
+fn resolve_file(llmd: &Path, name: &str) -> Result<PathBuf> {
+    match llmd_dir::find_file(llmd, name)? {
+        Some(path) => Ok(path),
+        None => bail!(
+            "File \"{name}\" not found in .llmd/. \
+             Run `llmd search {name}` to find it, or `llmd read catme` to browse available docs."
+        ),
+    }
 }
 
 /// Parses a "start:end" line range string.
@@ -1,13 +1,16 @@
 //! `llmd search` — full-text search across all .llmd/ files.
 //!
 //! Returns matching lines with file path, line number, and configurable context.
+//! Enclosing workspace layers are searched too; their paths are shown with a
+//! `<layer>:` prefix.
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use std::collections::BTreeSet;
 use std::fs;
 
-use crate::llmd_dir;
+use crate::commands::compose;
+use crate::{llmd_dir, usage};
 
 #[derive(Parser)]
 pub struct SearchArgs {
@@ -24,31 +27,36 @@ pub struct SearchArgs {
 }
 
 pub fn run(args: SearchArgs) -> Result<()> {
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
-
-    let search_root = match &args.dir {
-        Some(sub) => llmd.join(sub),
-        None => llmd.clone(),
-    };
-
-    if !search_root.exists() {
-        anyhow::bail!("Search directory does not exist: {}", search_root.display());
+    let llmd = llmd_dir::locate()?;
+
+    let layers = llmd_dir::layers(&llmd)?;
+    if let Some(sub) = &args.dir
+        && !layers.iter().any(|l| l.dir.join(sub).exists())
+    {
+        anyhow::bail!(
+            "Search directory does not exist: {}",
+            llmd.join(sub).display()
+        );
     }
 
     let re = regex_lite::Regex::new(&args.query)
         .with_context(|| format!("Invalid search pattern: {}", args.query))?;
 
-    let files = llmd_dir::list_all_files(&search_root);
+    let mut files = llmd_dir::list_layered_files(&llmd)?;
+    if let Some(sub) = &args.dir {
+        files.retain(|f| layers.iter().any(|l| f.starts_with(l.dir.join(sub))));
+    }
     if files.is_empty() {
-        eprintln!("No .md files found in {}", search_root.display());
+        eprintln!("No .md files found in {}", llmd.display());
         return Ok(());
     }
 
     let mut total_matches = 0usize;
+    let mut touched = Vec::new();
+    let mut shown = String::new();
 
     for file_path in &files {
-        let rel = file_path.strip_prefix(&llmd).unwrap_or(file_path);
+        let rel = llmd_dir::layer_path(&llmd, file_path);
         let content = match fs::read_to_string(file_path) {
             Ok(c) => c,
             Err(_) => continue,
@@ -71,7 +79,8 @@ pub fn run(args: SearchArgs) -> Result<()> {
         }
 
         if !context_indices.is_empty() {
-            println!("\n{}:", rel.display());
+            touched.push(compose::file_label(&llmd, file_path));
+            println!("\n{rel}:");
             let mut prev: Option<usize> = None;
             for &idx in &context_indices {
                 if let Some(p) = prev
@@ -85,11 +94,15 @@ pub fn run(args: SearchArgs) -> Result<()> {
                     " "
                 };
                 println!("  {marker} {:4}: {}", idx + 1, lines[idx]);
+                shown.push_str(lines[idx]);
+                shown.push('\n');
                 prev = Some(idx);
             }
         }
     }
 
+    usage::record(&llmd, "search", touched, &shown);
+
     if total_matches == 0 {
         eprintln!("No matches found for \"{}\"", args.query);
     } else {
@@ -24,8 +24,7 @@ pub struct ServeArgs {
 pub fn run(args: ServeArgs) -> Result<()> {
     ensure_mdbook()?;
 
-    let cwd = std::env::current_dir()?;
-    let llmd = llmd_dir::find(&cwd)?;
+    let llmd = llmd_dir::locate()?;
     let book_dir = super::build::generate_mdbook(&llmd)?;
 
     eprintln!(
@@ -0,0 +1,424 @@
+//! `llmd snapshot` — inspect, compare and replay composed-context snapshots.
+//!
+//! `llmd compose --snapshot` stores the composed document as
+//! `.llmd/snapshots/<id>/context.md` next to a `manifest.json` recording the
+//! compose [`Inputs`], the SHA-256 of every file the document was built from
+//! (included files too), and the llmd version. A copy of each input file is
+//! kept under `files/`, named by its hash, so `--since <snapshot>` can compare
+//! against it. Snapshot ids sort by creation time and may be abbreviated to
+//! any unique prefix.
+//!
+//! `diff` compares two snapshots' inputs, file hashes and documents; `replay`
+//! composes a snapshot's inputs again against the current docs.
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::commands::compose::{self, Composed, Inputs};
+use crate::issues::file_ops::now_iso;
+use crate::{diff, llmd_dir};
+
+/// File names inside a snapshot directory.
+const DOCUMENT: &str = "context.md";
+const MANIFEST: &str = "manifest.json";
+const FILES: &str = "files";
+
+#[derive(Parser)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    pub command: SnapshotCommand,
+}
+
+#[derive(Subcommand)]
+pub enum SnapshotCommand {
+    /// List snapshots, oldest first
+    List,
+    /// Compare two snapshots: inputs, file hashes and the documents themselves
+    Diff {
+        /// Older snapshot id (or unique prefix)
+        a: String,
+        /// Newer snapshot id (or unique prefix)
+        b: String,
+    },
+    /// Compose a snapshot's inputs again against the current docs
+    Replay {
+        /// Snapshot id (or unique prefix)
+        id: String,
+        /// Print a unified diff against the snapshot's document instead of the new document
+        #[arg(long)]
+        diff: bool,
+        /// Write the regenerated document to a file instead of stdout
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// `manifest.json`: what a snapshot's document was composed from.
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub id: String,
+    pub created_at: String,
+    pub llmd_version: String,
+    pub inputs: Inputs,
+    /// Labels of every section in the document, prerequisites included.
+    pub composed_sections: Vec<String>,
+    /// SHA-256 of each input file, keyed by its path as shown by `llmd index`.
+    pub files: BTreeMap<String, String>,
+    pub document_sha256: String,
+}
+
+pub fn run(args: SnapshotArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+
+    match args.command {
+        SnapshotCommand::List => {
+            let ids = list(&llmd)?;
+            if ids.is_empty() {
+                eprintln!("No snapshots yet. Create one with `llmd compose --snapshot`.");
+            }
+            for id in ids {
+                let manifest = load(&llmd, &id)?.0;
+                let what = match &manifest.inputs.issue {
+                    Some(issue) => format!("issue {issue}"),
+                    None => manifest
+                        .inputs
+                        .task
+                        .lines()
+                        .next()
+                        .unwrap_or("")
+                        .to_string(),
+                };
+                println!(
+                    "{id}  {} section(s)  {what}",
+                    manifest.composed_sections.len()
+                );
+            }
+        }
+        SnapshotCommand::Diff { a, b } => {
+            let (a, a_doc) = load(&llmd, &resolve(&llmd, &a)?)?;
+            let (b, b_doc) = load(&llmd, &resolve(&llmd, &b)?)?;
+            println!("Snapshots {} → {}", a.id, b.id);
+            if a.llmd_version != b.llmd_version {
+                println!("llmd version: {} → {}", a.llmd_version, b.llmd_version);
+            }
+            print_input_changes(&a.inputs, &b.inputs)?;
+            print_file_changes(&a.files, &b.files);
+            let patch = diff::unified(
+                &a_doc,
+                &b_doc,
+                &format!("{}/{DOCUMENT}", a.id),
+                &format!("{}/{DOCUMENT}", b.id),
+                3,
+            );
+            if patch.is_empty() {
+                println!("\nDocuments are identical.");
+            } else {
+                print!("\n{patch}");
+            }
+        }
+        SnapshotCommand::Replay { id, diff, output } => {
+            let (manifest, old_doc) = load(&llmd, &resolve(&llmd, &id)?)?;
+            if manifest.llmd_version != env!("CARGO_PKG_VERSION") {
+                eprintln!(
+                    "Note: snapshot was made with llmd {}, replaying with {}.",
+                    manifest.llmd_version,
+                    env!("CARGO_PKG_VERSION")
+                );
+            }
+            let mut inputs = manifest.inputs.clone();
+            let index = compose::build_section_index(
+                &llmd,
+                &llmd_dir::list_layered_files(&llmd)?,
+                &inputs.scope,
+            )?;
+            inputs.sections.retain(|label| {
+                let found = index.iter().any(|s| &s.label == label);
+                if !found {
+                    eprintln!("Warning: section \"{label}\" is no longer in the index; skipping.");
+                }
+                found
+            });
+
+            let composed = compose::compose(&llmd, &inputs)?;
+            let current = hash_files(&llmd, &composed.files)?;
+            if current != manifest.files {
+                eprintln!("Input files changed since the snapshot:");
+                print_file_changes_to_stderr(&manifest.files, &current);
+            }
+
+            let doc = if diff {
+                let patch = diff::unified(
+                    &old_doc,
+                    &composed.document,
+                    &format!("{}/{DOCUMENT}", manifest.id),
+                    "current",
+                    3,
+                );
+                if patch.is_empty() {
+                    eprintln!("The document is unchanged.");
+                }
+                patch
+            } else {
+                composed.document
+            };
+            match &output {
+                Some(path) => {
+                    fs::write(path, &doc)
+                        .with_context(|| format!("Cannot write to {}", path.display()))?;
+                    eprintln!(
+                        "Wrote {} to {}",
+                        if diff { "diff" } else { "document" },
+                        path.display()
+                    );
+                }
+                None => print!("{doc}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes `composed` and its manifest to a new snapshot directory and returns its id.
+pub fn save(llmd: &Path, inputs: &Inputs, composed: &Composed) -> Result<String> {
+    let created_at = now_iso();
+    let document_sha256 = sha256(composed.document.as_bytes());
+    // e.g. 20261018T224539Z-3f2a9c1e: sortable by time, distinct per document;
+    // `create_dir` adds a suffix if the same document was saved this second.
+    let stamp: String = created_at
+        .chars()
+        .take(19)
+        .filter(|c| !matches!(c, '-' | ':'))
+        .collect();
+    let files = hash_files(llmd, &composed.files)?;
+    let (id, dir) = create_dir(
+        &llmd_dir::snapshots_path(llmd),
+        &format!("{stamp}Z-{}", &document_sha256[..8]),
+    )?;
+
+    let manifest = Manifest {
+        id: id.clone(),
+        created_at,
+        llmd_version: env!("CARGO_PKG_VERSION").to_string(),
+        inputs: inputs.clone(),
+        composed_sections: composed.sections.iter().map(|s| s.label.clone()).collect(),
+        files,
+        document_sha256,
+    };
+
+    let blobs = dir.join(FILES);
+    fs::create_dir_all(&blobs).with_context(|| format!("Cannot create {}", blobs.display()))?;
+    for file in &composed.files {
+        let hash = &manifest.files[&llmd_dir::layer_path(llmd, file)];
+        fs::copy(file, blobs.join(hash))
+            .with_context(|| format!("Cannot copy {} into the snapshot", file.display()))?;
+    }
+    fs::write(dir.join(DOCUMENT), &composed.document)
+        .with_context(|| format!("Cannot write {}", dir.join(DOCUMENT).display()))?;
+    fs::write(
+        dir.join(MANIFEST),
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )
+    .with_context(|| format!("Cannot write {}", dir.join(MANIFEST).display()))?;
+    Ok(id)
+}
+
+/// Creates the directory for a new snapshot under `root` and returns its id
+/// and path. The same document saved twice within a second gets the same
+/// `id`; later saves get `<id>-2`, `<id>-3`, … rather than overwriting it.
+fn create_dir(root: &Path, id: &str) -> Result<(String, PathBuf)> {
+    fs::create_dir_all(root).with_context(|| format!("Cannot create {}", root.display()))?;
+    for n in 1.. {
+        let id = if n == 1 {
+            id.to_string()
+        } else {
+            format!("{id}-{n}")
+        };
+        let dir = root.join(&id);
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok((id, dir)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Cannot create {}", dir.display()));
+            }
+        }
+    }
+    unreachable!()
+}
+
+/// Snapshot ids, oldest first.
+pub fn list(llmd: &Path) -> Result<Vec<String>> {
+    let dir = llmd_dir::snapshots_path(llmd);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut ids: Vec<String> = fs::read_dir(&dir)
+        .with_context(|| format!("Cannot read {}", dir.display()))?
+        .flatten()
+        .filter(|e| e.path().join(MANIFEST).is_file())
+        .filter_map(|e| e.file_name().to_str().map(str::to_string))
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Resolves a snapshot id or unique id prefix.
+pub fn resolve(llmd: &Path, reference: &str) -> Result<String> {
+    let ids = list(llmd)?;
+    if ids.iter().any(|id| id == reference) {
+        return Ok(reference.to_string());
+    }
+    let matches: Vec<&String> = ids.iter().filter(|id| id.starts_with(reference)).collect();
+    match matches.as_slice() {
+        [id] => Ok((*id).clone()),
+        [] => bail!("No snapshot \"{reference}\". Run `llmd snapshot list` to see snapshots."),
+        _ => bail!(
+            "Snapshot \"{reference}\" is ambiguous: {}",
+            matches
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Path of the stored copy of an input file with the given hash.
+pub fn blob_path(llmd: &Path, id: &str, hash: &str) -> PathBuf {
+    llmd_dir::snapshots_path(llmd)
+        .join(id)
+        .join(FILES)
+        .join(hash)
+}
+
+/// Reads a snapshot's manifest and document.
+pub fn load(llmd: &Path, id: &str) -> Result<(Manifest, String)> {
+    let dir = llmd_dir::snapshots_path(llmd).join(id);
+    let manifest = fs::read_to_string(dir.join(MANIFEST))
+        .with_context(|| format!("Cannot read {}", dir.join(MANIFEST).display()))?;
+    let manifest: Manifest = serde_json::from_str(&manifest)
+        .with_context(|| format!("Invalid {}", dir.join(MANIFEST).display()))?;
+    let document = fs::read_to_string(dir.join(DOCUMENT))
+        .with_context(|| format!("Cannot read {}", dir.join(DOCUMENT).display()))?;
+    Ok((manifest, document))
+}
+
+fn hash_files(llmd: &Path, files: &[PathBuf]) -> Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    for file in files {
+        let bytes = fs::read(file).with_context(|| format!("Cannot read {}", file.display()))?;
+        hashes.insert(llmd_dir::layer_path(llmd, file), sha256(&bytes));
+    }
+    Ok(hashes)
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Prints each top-level input field that differs, as compact JSON.
+fn print_input_changes(a: &Inputs, b: &Inputs) -> Result<()> {
+    let (Value::Object(a), Value::Object(b)) = (serde_json::to_value(a)?, serde_json::to_value(b)?)
+    else {
+        return Ok(());
+    };
+    let changed: Vec<(&String, &Value, &Value)> = a
+        .iter()
+        .filter_map(|(key, old)| {
+            let new = b.get(key).unwrap_or(&Value::Null);
+            (old != new).then_some((key, old, new))
+        })
+        .collect();
+    if changed.is_empty() {
+        println!("Inputs: unchanged");
+    } else {
+        println!("Inputs:");
+        for (key, old, new) in changed {
+            println!("  {key}: {old} → {new}");
+        }
+    }
+    Ok(())
+}
+
+/// `M`, `A` and `D` lines for files whose hash changed, appeared or disappeared.
+fn file_changes(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (file, hash) in a {
+        match b.get(file) {
+            Some(other) if other != hash => lines.push(format!("  M {file}")),
+            Some(_) => {}
+            None => lines.push(format!("  D {file}")),
+        }
+    }
+    lines.extend(
+        b.keys()
+            .filter(|f| !a.contains_key(*f))
+            .map(|f| format!("  A {f}")),
+    );
+    lines
+}
+
+fn print_file_changes(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>) {
+    let lines = file_changes(a, b);
+    if lines.is_empty() {
+        println!("Files: unchanged");
+    } else {
+        println!("Files:");
+        for line in lines {
+            println!("{line}");
+        }
+    }
+}
+
+fn print_file_changes_to_stderr(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>) {
+    for line in file_changes(a, b) {
+        eprintln!("{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_changes() {
+        let a = BTreeMap::from([
+            ("api.md".to_string(), "1".to_string()),
+            ("old.md".to_string(), "2".to_string()),
+            ("same.md".to_string(), "3".to_string()),
+        ]);
+        let b = BTreeMap::from([
+            ("api.md".to_string(), "9".to_string()),
+            ("new.md".to_string(), "4".to_string()),
+            ("same.md".to_string(), "3".to_string()),
+        ]);
+        assert_eq!(
+            file_changes(&a, &b),
+            vec!["  M api.md", "  D old.md", "  A new.md"]
+        );
+    }
+
+    #[test]
+    fn test_create_dir() {
+        let root = std::env::temp_dir().join(format!("llmd-snapshots-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let ids: Vec<String> = (0..3)
+            .map(|_| create_dir(&root, "20261018T224539Z-3f2a9c1e").unwrap().0)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "20261018T224539Z-3f2a9c1e",
+                "20261018T224539Z-3f2a9c1e-2",
+                "20261018T224539Z-3f2a9c1e-3"
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
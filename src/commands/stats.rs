@@ -0,0 +1,322 @@
+//! `llmd stats` — report how large the knowledge base is and where the bulk lives.
+//!
+//! Lists every file's token count and number of indexed headings, the largest
+//! sections in the index, files with no indexable headings (which `index` and
+//! `compose --sections` cannot reach), the split between authored, imported
+//! and pack material, and the cost of what every composed document carries
+//! regardless of selection: the catme.md excerpt. Counts use the tokenizer
+//! selected in `.llmd/llmd.json`. `--json` emits the same report.
+
+use anyhow::Result;
+use clap::Parser;
+use serde_json::json;
+use std::path::Path;
+
+use crate::commands::compose::{self, ScopeArgs};
+use crate::config::{Config, IndexConfig};
+use crate::tokenizer::Tokenizer;
+use crate::{llmd_dir, markdown, topic};
+
+#[derive(Parser)]
+pub struct StatsArgs {
+    /// Number of largest sections to list
+    #[arg(long, default_value_t = 10, value_name = "N")]
+    pub top: usize,
+
+    /// Print the report as JSON (every section, not just the largest)
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub scope: ScopeArgs,
+}
+
+/// Token count and heading count for one file.
+struct FileStats {
+    label: String,
+    kind: &'static str,
+    tokens: usize,
+    /// Headings between H2 and the index depth.
+    headings: usize,
+    /// Whether the file is considered by the section index at all.
+    indexed: bool,
+}
+
+/// Everything `llmd stats` reports, before formatting.
+struct Report {
+    /// Largest first.
+    files: Vec<FileStats>,
+    /// Section labels and token counts, largest first.
+    sections: Vec<(String, usize)>,
+    /// Tokens of the catme.md excerpt every composed document carries.
+    catme_tokens: usize,
+}
+
+impl Report {
+    fn total(&self, kind: &str) -> usize {
+        self.files
+            .iter()
+            .filter(|f| f.kind == kind)
+            .map(|f| f.tokens)
+            .sum()
+    }
+
+    /// Indexed files with no headings in range.
+    fn headingless(&self) -> Vec<&FileStats> {
+        self.files
+            .iter()
+            .filter(|f| f.indexed && f.headings == 0)
+            .collect()
+    }
+}
+
+pub fn run(args: StatsArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let config = llmd_dir::config(&llmd)?;
+    let scope = args.scope.resolve(&config.index);
+    let tokenizer = Tokenizer::for_project(&llmd)?;
+    let report = report(&llmd, &config, &scope, &tokenizer)?;
+    let (files, sections) = (&report.files, &report.sections);
+    let catme_tokens = report.catme_tokens;
+
+    // catme.md is written by the project, so it counts as authored.
+    let authored = report.total("authored") + report.total("catme");
+    let imported = report.total("imported");
+    let packs = report.total("pack");
+    let headingless = report.headingless();
+
+    if args.json {
+        let out = json!({
+            "exact": !matches!(tokenizer, Tokenizer::Heuristic),
+            "total_tokens": authored + imported + packs,
+            "files": files.iter().map(|f| json!({
+                "file": f.label,
+                "kind": f.kind,
+                "tokens": f.tokens,
+                "headings": f.headings,
+                "indexed": f.indexed,
+            })).collect::<Vec<_>>(),
+            "sections": sections.iter().map(|(label, tokens)| json!({
+                "label": label,
+                "tokens": tokens,
+            })).collect::<Vec<_>>(),
+            "no_headings": headingless.iter().map(|f| &f.label).collect::<Vec<_>>(),
+            "authored_tokens": authored,
+            "imported_tokens": imported,
+            "pack_tokens": packs,
+            "imported_share": share(imported, authored + imported),
+            "always_included": {
+                "catme_excerpt": catme_tokens,
+                "catme_sections": config.compose.catme_sections,
+            },
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    if matches!(tokenizer, Tokenizer::Heuristic) {
+        eprintln!(
+            "Token counts are estimates (~4 bytes per token); see `tokenizer` in llmd.json.\n"
+        );
+    }
+
+    println!(
+        "Files ({}, {} tokens)",
+        files.len(),
+        authored + imported + packs
+    );
+    println!("  {:>7}  {:>8}  {:<8}  FILE", "TOKENS", "HEADINGS", "KIND");
+    for f in files {
+        println!(
+            "  {:>7}  {:>8}  {:<8}  {}",
+            f.tokens, f.headings, f.kind, f.label
+        );
+    }
+
+    println!("\nLargest sections");
+    if sections.is_empty() {
+        println!("  (no indexed sections)");
+    }
+    for (label, tokens) in sections.iter().take(args.top) {
+        println!("  {tokens:>7}  {label}");
+    }
+
+    println!("\nFiles with no headings (not reachable through `llmd index`)");
+    if headingless.is_empty() {
+        println!("  (none)");
+    }
+    for f in &headingless {
+        println!("  {:>7}  {}", f.tokens, f.label);
+    }
+
+    println!("\nImported vs authored");
+    println!(
+        "  authored  {authored:>7}  ({:.0}%)",
+        100.0 - share(imported, authored + imported) * 100.0
+    );
+    println!(
+        "  imported  {imported:>7}  ({:.0}%)",
+        share(imported, authored + imported) * 100.0
+    );
+    if packs > 0 {
+        println!("  packs     {packs:>7}");
+    }
+
+    println!("\nAlways included in `llmd compose`");
+    println!(
+        "  {catme_tokens:>7}  catme excerpt ({})",
+        config.compose.catme_sections.join(", ")
+    );
+    Ok(())
+}
+
+/// Counts tokens and headings per file and tokens per indexed section.
+fn report(
+    llmd: &Path,
+    config: &Config,
+    scope: &IndexConfig,
+    tokenizer: &Tokenizer,
+) -> Result<Report> {
+    let all_files = llmd_dir::list_layered_files(llmd)?;
+
+    let mut files = Vec::new();
+    for file in &all_files {
+        let Some(kind) = kind_of(llmd, file) else {
+            continue;
+        };
+        let body = topic::load_lenient(llmd, file)?.body;
+        files.push(FileStats {
+            label: compose::file_label(llmd, file),
+            kind,
+            tokens: tokenizer.count(&body),
+            headings: markdown::list_headings(&body)
+                .iter()
+                .filter(|(depth, _)| (2..=scope.depth).contains(depth))
+                .count(),
+            indexed: compose::is_topic_file(llmd, file, scope),
+        });
+    }
+    files.sort_by_key(|f| std::cmp::Reverse(f.tokens));
+
+    let mut sections: Vec<(String, usize)> = compose::build_section_index(llmd, &all_files, scope)?
+        .into_iter()
+        .map(|s| (s.label, tokenizer.count(&s.text)))
+        .collect();
+    sections.sort_by_key(|&(_, tokens)| std::cmp::Reverse(tokens));
+
+    let catme_path = llmd_dir::catme_path(llmd);
+    let catme_tokens =
+        if catme_path.is_file() && config.compose.layout.iter().any(|b| b == "overview") {
+            let catme = topic::load(llmd, &catme_path)?.body;
+            tokenizer.count(&compose::extract_catme_excerpt(
+                &catme,
+                &config.compose.catme_sections,
+            ))
+        } else {
+            0
+        };
+
+    Ok(Report {
+        files,
+        sections,
+        catme_tokens,
+    })
+}
+
+/// Classifies a file as `catme`, `imported`, `pack` or `authored`. Issues and
+/// the generated mdbook trees are not knowledge and return `None`.
+fn kind_of(llmd: &Path, file: &Path) -> Option<&'static str> {
+    let layer = llmd_dir::layer_of(llmd, file);
+    if layer.as_ref().is_some_and(|l| l.pack) {
+        return Some("pack");
+    }
+    let dir = layer.map_or_else(|| llmd.to_path_buf(), |l| l.dir);
+    if file.starts_with(llmd_dir::issues_path(&dir))
+        || file.starts_with(dir.join(".mdbook"))
+        || file.starts_with(dir.join("book"))
+    {
+        None
+    } else if file == llmd_dir::catme_path(&dir) {
+        Some("catme")
+    } else if file.starts_with(dir.join("imported")) {
+        Some("imported")
+    } else {
+        Some("authored")
+    }
+}
+
+/// `part` as a fraction of `whole`, or 0 when `whole` is 0.
+fn share(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_report() {
+        let fx = Fixture::new(
+            "stats",
+            &[
+                (
+                    "catme.md",
+                    "# Demo\n\n## Project Summary\n\nA demo.\n\n## Notes\n\nNot in the excerpt.\n",
+                ),
+                (
+                    "auth.md",
+                    "# Auth\n\n## Tokens\n\nIssued on login and refreshed hourly.\n\n\
+                     ### Refresh\n\nEvery hour.\n\n#### Deep\n\nBelow the index depth.\n",
+                ),
+                ("notes.md", "Just prose, no headings at all.\n"),
+                ("imported/CLAUDE.md", "Imported prose.\n"),
+                ("issues/1-x.md", "---\ntitle: x\n---\n"),
+            ],
+        );
+        let llmd = fx.llmd();
+        let config = Config::default();
+        let report = report(&llmd, &config, &config.index, &Tokenizer::Heuristic).unwrap();
+
+        let files: Vec<(&str, &str, usize, usize)> = report
+            .files
+            .iter()
+            .map(|f| (f.label.as_str(), f.kind, f.tokens, f.headings))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("auth", "authored", 30, 2),
+                ("catme", "catme", 17, 2),
+                ("notes", "authored", 8, 0),
+                ("imported/CLAUDE", "imported", 4, 0),
+            ]
+        );
+        assert_eq!(
+            report.sections,
+            vec![
+                ("auth > Tokens".to_string(), 28),
+                ("auth > Refresh".to_string(), 15),
+            ]
+        );
+        assert_eq!(report.catme_tokens, 8);
+        assert_eq!(report.total("authored"), 38);
+        // Imported files are outside the index, so only notes.md is unreachable.
+        let headingless: Vec<&str> = report
+            .headingless()
+            .iter()
+            .map(|f| f.label.as_str())
+            .collect();
+        assert_eq!(headingless, vec!["notes"]);
+    }
+
+    #[test]
+    fn test_share() {
+        assert_eq!(share(1, 4), 0.25);
+        assert_eq!(share(0, 0), 0.0);
+    }
+}
@@ -0,0 +1,267 @@
+//! `llmd usage report` — aggregate the opt-in usage log.
+//!
+//! Reads `.llmd/.usage.jsonl` (written by `read`, `compose` and `search` when
+//! `usage.log` is enabled) and reports how often each section or file was
+//! touched, activity per day, and the indexed sections nobody has used. A
+//! section counts as used when it was composed or read on its own, or when
+//! its whole file was read, included or matched by a search.
+
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::commands::compose::{self, IndexedSection};
+use crate::usage::Entry;
+use crate::{llmd_dir, usage};
+
+#[derive(Parser)]
+pub struct UsageArgs {
+    #[command(subcommand)]
+    pub command: UsageCommand,
+}
+
+#[derive(Subcommand)]
+pub enum UsageCommand {
+    /// Summarise the usage log by section and by day, and list unused sections
+    Report {
+        /// Only count entries on or after this date (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Uses, tokens and last use of one section or file.
+#[derive(Default)]
+struct Tally {
+    uses: usize,
+    tokens: usize,
+    last: String,
+}
+
+/// Runs per command, and tokens, for one day.
+#[derive(Default)]
+struct Day {
+    commands: BTreeMap<String, usize>,
+    tokens: usize,
+}
+
+/// The usage log aggregated against the current section index.
+struct Report {
+    by_label: BTreeMap<String, Tally>,
+    /// Keyed by `YYYY-MM-DD`.
+    by_day: BTreeMap<String, Day>,
+    /// Labels of indexed sections no entry touched, in index order.
+    unused: Vec<String>,
+}
+
+pub fn run(args: UsageArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let UsageCommand::Report { since, json } = args.command;
+    if let Some(since) = &since
+        && !is_date(since)
+    {
+        bail!("Invalid --since \"{since}\": expected YYYY-MM-DD");
+    }
+
+    let (mut entries, skipped) = usage::load(&llmd)?;
+    if skipped > 0 {
+        eprintln!("Warning: skipped {skipped} malformed line(s) in the usage log.");
+    }
+    if let Some(since) = &since {
+        entries.retain(|e| e.ts.as_str() >= since.as_str());
+    }
+    if entries.is_empty() && !json {
+        if llmd_dir::config(&llmd)?.usage.log {
+            eprintln!("No usage recorded yet.");
+        } else {
+            eprintln!("Usage logging is off. Enable it with `llmd config set usage.log true`.");
+        }
+        return Ok(());
+    }
+
+    let config = llmd_dir::config(&llmd)?;
+    let index =
+        compose::build_section_index(&llmd, &llmd_dir::list_layered_files(&llmd)?, &config.index)?;
+    let Report {
+        by_label,
+        by_day,
+        unused,
+    } = report(&llmd, &entries, &index);
+
+    let mut ranked: Vec<(&String, &Tally)> = by_label.iter().collect();
+    ranked.sort_by_key(|&(_, t)| std::cmp::Reverse(t.uses));
+
+    if json {
+        let out = json!({
+            "entries": entries.len(),
+            "sections": ranked.iter().map(|(label, t)| json!({
+                "label": label,
+                "uses": t.uses,
+                "tokens": t.tokens,
+                "last_used": t.last,
+            })).collect::<Vec<_>>(),
+            "days": by_day.iter().map(|(day, d)| json!({
+                "day": day,
+                "commands": d.commands,
+                "tokens": d.tokens,
+            })).collect::<Vec<_>>(),
+            "never_used": unused,
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    println!("By section ({} runs)", entries.len());
+    println!(
+        "  {:>5}  {:>7}  {:<10}  SECTION",
+        "USES", "TOKENS", "LAST USED"
+    );
+    for (label, t) in &ranked {
+        println!(
+            "  {:>5}  {:>7}  {:<10}  {label}",
+            t.uses,
+            t.tokens,
+            t.last.get(..10).unwrap_or(&t.last)
+        );
+    }
+
+    println!("\nBy day");
+    println!(
+        "  {:<10}  {:>5}  {:>7}  {:>6}  {:>7}",
+        "DAY", "READ", "COMPOSE", "SEARCH", "TOKENS"
+    );
+    for (day, d) in &by_day {
+        let runs = |c: &str| d.commands.get(c).copied().unwrap_or(0);
+        println!(
+            "  {day:<10}  {:>5}  {:>7}  {:>6}  {:>7}",
+            runs("read"),
+            runs("compose"),
+            runs("search"),
+            d.tokens
+        );
+    }
+
+    println!(
+        "\nNever used ({} of {} indexed sections)",
+        unused.len(),
+        index.len()
+    );
+    for label in &unused {
+        println!("  {label}");
+    }
+    Ok(())
+}
+
+/// Tallies `entries` by label and by day, and finds the sections in `index`
+/// that none of them touched.
+fn report(llmd: &Path, entries: &[Entry], index: &[IndexedSection]) -> Report {
+    let mut by_label: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut by_day: BTreeMap<String, Day> = BTreeMap::new();
+    for entry in entries {
+        for label in &entry.touched {
+            let tally = by_label.entry(label.clone()).or_default();
+            tally.uses += 1;
+            // Attribute the output evenly to everything it touched.
+            tally.tokens += entry.tokens / entry.touched.len();
+            if entry.ts > tally.last {
+                tally.last = entry.ts.clone();
+            }
+        }
+        let day = by_day
+            .entry(entry.ts.get(..10).unwrap_or(&entry.ts).to_string())
+            .or_default();
+        *day.commands.entry(entry.command.clone()).or_default() += 1;
+        day.tokens += entry.tokens;
+    }
+
+    let unused = index
+        .iter()
+        .filter(|s| {
+            !by_label.contains_key(&s.label)
+                && !by_label.contains_key(&compose::file_label(llmd, &s.file))
+        })
+        .map(|s| s.label.clone())
+        .collect();
+    Report {
+        by_label,
+        by_day,
+        unused,
+    }
+}
+
+/// True for `YYYY-MM-DD`.
+pub(crate) fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && b.iter()
+            .enumerate()
+            .all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IndexConfig;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn test_report() {
+        let log = [
+            r#"{"ts":"2026-10-01T09:00:00Z","command":"read","touched":["auth > Tokens"],"tokens":10}"#,
+            r#"{"ts":"2026-10-02T09:00:00Z","command":"compose","touched":["auth > Tokens","auth > Refresh"],"tokens":30}"#,
+            "not json",
+            r#"{"ts":"2026-10-02T10:00:00Z","command":"search","touched":["notes"],"tokens":4}"#,
+        ];
+        let fx = Fixture::new(
+            "usage",
+            &[
+                ("auth.md", "# Auth\n\n## Tokens\n\nt\n\n### Refresh\n\nr\n"),
+                ("notes.md", "# Notes\n\n## Setup\n\ns\n"),
+                ("other.md", "# Other\n\n## Lonely\n\nl\n"),
+                (usage::FILE_NAME, &log.join("\n")),
+            ],
+        );
+        let llmd = fx.llmd();
+        let (entries, skipped) = usage::load(&llmd).unwrap();
+        assert_eq!((entries.len(), skipped), (3, 1));
+        let files = llmd_dir::list_layered_files(&llmd).unwrap();
+        let index = compose::build_section_index(&llmd, &files, &IndexConfig::default()).unwrap();
+        let report = report(&llmd, &entries, &index);
+
+        let tallies: Vec<(&str, usize, usize, &str)> = report
+            .by_label
+            .iter()
+            .map(|(label, t)| (label.as_str(), t.uses, t.tokens, t.last.as_str()))
+            .collect();
+        assert_eq!(
+            tallies,
+            vec![
+                ("auth > Refresh", 1, 15, "2026-10-02T09:00:00Z"),
+                ("auth > Tokens", 2, 25, "2026-10-02T09:00:00Z"),
+                ("notes", 1, 4, "2026-10-02T10:00:00Z"),
+            ]
+        );
+        let days: Vec<(&str, usize, usize)> = report
+            .by_day
+            .iter()
+            .map(|(day, d)| (day.as_str(), d.commands.values().sum(), d.tokens))
+            .collect();
+        assert_eq!(days, vec![("2026-10-01", 1, 10), ("2026-10-02", 2, 34)]);
+        // A search hit on notes.md counts for its sections.
+        assert_eq!(report.unused, vec!["other > Lonely"]);
+    }
+
+    #[test]
+    fn test_is_date() {
+        assert!(is_date("2026-10-18"));
+        assert!(!is_date("2026-1-18"));
+        assert!(!is_date("yesterday"));
+    }
+}
@@ -0,0 +1,207 @@
+//! `llmd watch` — keep artefacts derived from .llmd/ up to date.
+//!
+//! Polls .llmd/ (and, with `--source-paths` or `watch.source_paths`, the source
+//! paths listed in catme.md's Context Map) for changes. Once a burst of edits
+//! has been quiet for `watch.debounce_ms`, it regenerates the mdbook tree —
+//! rewriting only pages whose content changed — and runs each configured
+//! generator whose `on` globs match a changed path. Polling keeps this free of
+//! platform-specific notification APIs.
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::{self, Config};
+use crate::{llmd_dir, markdown, usage};
+
+/// How often the watched trees are scanned.
+const POLL: Duration = Duration::from_millis(250);
+
+#[derive(Parser)]
+pub struct WatchArgs {
+    /// Also watch the source paths listed in catme.md's Context Map
+    #[arg(long)]
+    pub source_paths: bool,
+
+    /// Regenerate everything once and exit instead of watching
+    #[arg(long)]
+    pub once: bool,
+}
+
+/// Modification time and size of every watched file.
+type Snapshot = BTreeMap<PathBuf, (SystemTime, u64)>;
+
+pub fn run(args: WatchArgs) -> Result<()> {
+    let llmd = llmd_dir::locate()?;
+    let mut config = llmd_dir::config(&llmd)?;
+    let project = llmd.parent().unwrap_or(&llmd).to_path_buf();
+
+    if args.once {
+        return regenerate(&llmd, &project, &config, None);
+    }
+    // A failing first pass is reported like any other; watching goes on.
+    if let Err(e) = regenerate(&llmd, &project, &config, None) {
+        eprintln!("Error: {e:#}");
+    }
+
+    let source_paths = |config: &Config| args.source_paths || config.watch.source_paths;
+    let mut previous = snapshot(&llmd, &project, source_paths(&config));
+    eprintln!("Watching {} file(s) — press Ctrl+C to stop", previous.len());
+
+    let mut changed = BTreeSet::new();
+    let mut last_change = Instant::now();
+    loop {
+        std::thread::sleep(POLL);
+        let current = snapshot(&llmd, &project, source_paths(&config));
+        if current != previous {
+            changed.extend(diff(&previous, &current));
+            previous = current;
+            last_change = Instant::now();
+            continue;
+        }
+        if changed.is_empty()
+            || last_change.elapsed() < Duration::from_millis(config.watch.debounce_ms)
+        {
+            continue;
+        }
+
+        let names: Vec<String> = changed.iter().map(|p| relative(&project, p)).collect();
+        eprintln!("\nChanged: {}", names.join(", "));
+        if changed.contains(&config::path(&llmd)) {
+            match llmd_dir::reload_config(&llmd) {
+                Ok(new) => {
+                    config = new;
+                    eprintln!("Reloaded {}", config::FILE_NAME);
+                }
+                Err(e) => eprintln!("Error: {e:#} (keeping the previous config)"),
+            }
+        }
+        if let Err(e) = regenerate(&llmd, &project, &config, Some(&changed)) {
+            eprintln!("Error: {e:#}");
+        }
+        changed.clear();
+        // Absorb whatever the generators wrote so it doesn't retrigger them.
+        previous = snapshot(&llmd, &project, source_paths(&config));
+    }
+}
+
+/// Regenerates the configured targets. `changed` is `None` on the first pass,
+/// which runs everything.
+fn regenerate(
+    llmd: &Path,
+    project: &Path,
+    config: &Config,
+    changed: Option<&BTreeSet<PathBuf>>,
+) -> Result<()> {
+    let touches_llmd = changed.is_none_or(|c| c.iter().any(|p| p.starts_with(llmd)));
+    if config.watch.mdbook && touches_llmd {
+        let (_, count) = super::build::update_mdbook(llmd)?;
+        eprintln!("mdbook: {count} file(s) updated in .llmd/.mdbook/");
+    }
+
+    let mut failed = Vec::new();
+    for generator in &config.watch.generators {
+        let triggered = match changed {
+            None => true,
+            Some(_) if generator.on.is_empty() => true,
+            Some(changed) => changed.iter().any(|p| {
+                let rel = relative(project, p);
+                generator
+                    .on
+                    .iter()
+                    .any(|pattern| llmd_dir::glob_match(pattern, &rel))
+            }),
+        };
+        if !triggered {
+            continue;
+        }
+        eprintln!("Running: {}", generator.command);
+        let status = shell(&generator.command)
+            .current_dir(project)
+            .status()
+            .with_context(|| format!("Failed to run `{}`", generator.command))?;
+        if !status.success() {
+            eprintln!("`{}` exited with status {status}", generator.command);
+            failed.push(generator.command.as_str());
+        }
+    }
+    if !failed.is_empty() {
+        bail!(
+            "{} generator(s) failed: {}",
+            failed.len(),
+            failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Scans .llmd/ (minus the generated mdbook directories, snapshots, vendored
+/// packs and the usage log) and, if requested, the Context Map's source paths.
+fn snapshot(llmd: &Path, project: &Path, source_paths: bool) -> Snapshot {
+    let mut roots = vec![llmd.to_path_buf()];
+    if source_paths && let Ok(catme) = fs::read_to_string(llmd_dir::catme_path(llmd)) {
+        roots.extend(
+            markdown::context_map_paths(&catme)
+                .iter()
+                .map(|p| project.join(p)),
+        );
+    }
+    let skip = [
+        llmd.join(".mdbook"),
+        llmd.join("book"),
+        llmd_dir::snapshots_path(llmd),
+        llmd_dir::packs_path(llmd),
+        usage::path(llmd),
+    ];
+
+    let mut files = Snapshot::new();
+    for root in roots {
+        let walker = walkdir::WalkDir::new(&root)
+            .into_iter()
+            .filter_entry(|e| !skip.iter().any(|s| e.path() == s) && e.file_name() != ".git");
+        for entry in walker.flatten() {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            if let Ok(meta) = entry.metadata() {
+                let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                files.insert(entry.into_path(), (modified, meta.len()));
+            }
+        }
+    }
+    files
+}
+
+/// Paths created, modified or deleted between two snapshots.
+fn diff(before: &Snapshot, after: &Snapshot) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(stamp))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(before.keys().filter(|p| !after.contains_key(*p)).cloned());
+    changed
+}
+
+fn relative(project: &Path, path: &Path) -> String {
+    path.strip_prefix(project)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
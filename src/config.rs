@@ -0,0 +1,499 @@
+//! Project configuration stored in `.llmd/llmd.json`.
+//!
+//! The file is optional; every key has a default. Unknown keys are rejected so
+//! that typos surface as errors instead of being silently ignored. Commands get
+//! the config through `llmd_dir::config()`, which loads it once per process.
+//!
+//! `context-mappings.json` and the issue tracker keep their own files; their
+//! locations are recorded under `paths` so tools can discover them from here.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// File name of the project config inside `.llmd/`.
+pub const FILE_NAME: &str = "llmd.json";
+
+/// Blocks that `compose.layout` may contain, in their default order.
+pub const LAYOUT_BLOCKS: &[&str] = &["overview", "includes", "sections"];
+
+/// Top-level project configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub index: IndexConfig,
+    pub compose: ComposeConfig,
+    pub paths: PathsConfig,
+    pub workspace: WorkspaceConfig,
+    /// External knowledge packs, by name (see `llmd pack`).
+    pub packs: BTreeMap<String, PackConfig>,
+    pub watch: WatchConfig,
+    pub tokenizer: TokenizerConfig,
+    pub usage: UsageConfig,
+}
+
+/// Controls which headings and files make up the section index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexConfig {
+    /// Deepest heading level to index (2–4). H2 is always the shallowest.
+    pub depth: usize,
+    /// Index files in `imported/` (e.g. imported CLAUDE.md, AGENTS.md).
+    pub include_imported: bool,
+    /// Globs, relative to `.llmd/`, of files to leave out of the index.
+    pub exclude: Vec<String>,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            include_imported: false,
+            exclude: Vec::new(),
+        }
+    }
+}
+
+/// Controls the shape of documents produced by `llmd compose`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComposeConfig {
+    /// catme.md sections (heading substrings) quoted in the Project Overview.
+    pub catme_sections: Vec<String>,
+    /// Order of the document blocks after the task header. Leave a block out to drop it.
+    pub layout: Vec<String>,
+}
+
+impl Default for ComposeConfig {
+    fn default() -> Self {
+        Self {
+            catme_sections: vec![
+                "Project Summary".to_string(),
+                "Technology Stack".to_string(),
+                "Build".to_string(),
+            ],
+            layout: LAYOUT_BLOCKS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// Locations of the other llmd data files, relative to `.llmd/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    /// Label-to-topics mapping used by `llmd compose --issue`.
+    pub context_mappings: String,
+    /// Issue tracker directory (holds its own `config.json`).
+    pub issues: String,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            context_mappings: "context-mappings.json".to_string(),
+            issues: "issues".to_string(),
+        }
+    }
+}
+
+/// Controls how this `.llmd/` layers over `.llmd/` directories further up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    /// Also read the nearest enclosing `.llmd/` (and its ancestors).
+    pub inherit: bool,
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        Self { inherit: true }
+    }
+}
+
+/// An external, read-only `.llmd/` source. Exactly one of `path` and `git` is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackConfig {
+    /// Local directory, absolute or relative to the project root, read in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Git URL, vendored into `.llmd/packs/<name>/` by `llmd pack add/update`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// Branch, tag or commit to check out (default: the remote's HEAD).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+}
+
+/// Controls what `llmd watch` regenerates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchConfig {
+    /// Keep the mdbook tree in `.llmd/.mdbook/` (including the roadmap) up to date.
+    pub mdbook: bool,
+    /// Also watch the source paths listed in catme.md's Context Map.
+    pub source_paths: bool,
+    /// Quiet period after the last change before regenerating, in milliseconds.
+    pub debounce_ms: u64,
+    /// Shell commands to run when matching files change.
+    pub generators: Vec<GeneratorConfig>,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            mdbook: true,
+            source_paths: false,
+            debounce_ms: 500,
+            generators: Vec::new(),
+        }
+    }
+}
+
+/// A user-defined command run by `llmd watch`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratorConfig {
+    /// Shell command, run from the project root.
+    pub command: String,
+    /// Globs, relative to the project root, that trigger the command. Empty
+    /// means any watched change.
+    #[serde(default)]
+    pub on: Vec<String>,
+}
+
+/// Selects how token counts are computed (`read --tokens`, `index --tokens`,
+/// `compose --budget`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenizerConfig {
+    pub mode: TokenizerMode,
+    /// tiktoken-format vocabulary (e.g. `cl100k_base.tiktoken`), absolute or
+    /// relative to the project root. Required for `bpe`.
+    pub vocab: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenizerMode {
+    /// One token per four bytes.
+    #[default]
+    Heuristic,
+    /// Byte-pair encoding with the vocabulary in `vocab`.
+    Bpe,
+}
+
+/// The opt-in local usage log (see `usage`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsageConfig {
+    /// Append a line to `.llmd/.usage.jsonl` for every read, compose and search.
+    pub log: bool,
+}
+
+/// Returns the path to `llmd.json` inside the given `.llmd/` directory.
+pub fn path(llmd: &Path) -> PathBuf {
+    llmd.join(FILE_NAME)
+}
+
+/// Loads and validates `.llmd/llmd.json`, or returns the defaults if it does not exist.
+pub fn load(llmd: &Path) -> Result<Config> {
+    let path = path(llmd);
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let value: Value =
+        serde_json::from_str(&content).with_context(|| format!("Invalid {}", path.display()))?;
+    from_value(value).with_context(|| format!("Invalid {}", path.display()))
+}
+
+/// Applies `edit` to the raw JSON of `llmd.json` (an empty object if the file
+/// does not exist), validates the result and writes it back. Keys the user
+/// never set stay out of the file.
+pub fn update(llmd: &Path, edit: impl FnOnce(&mut Value) -> Result<()>) -> Result<()> {
+    let path = path(llmd);
+    let mut file = if path.exists() {
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid {}", path.display()))?
+    } else {
+        Value::Object(serde_json::Map::new())
+    };
+    edit(&mut file)?;
+    from_value(file.clone()).with_context(|| format!("Refusing to write {}", path.display()))?;
+    fs::write(&path, serde_json::to_string_pretty(&file)? + "\n")
+        .with_context(|| format!("Cannot write {}", path.display()))
+}
+
+/// Deserializes and validates a config from raw JSON.
+pub fn from_value(value: Value) -> Result<Config> {
+    let config: Config = serde_json::from_value(value)?;
+    validate(&config)?;
+    Ok(config)
+}
+
+fn validate(config: &Config) -> Result<()> {
+    if !(2..=4).contains(&config.index.depth) {
+        bail!(
+            "index.depth must be between 2 and 4 (got {})",
+            config.index.depth
+        );
+    }
+    if config
+        .compose
+        .catme_sections
+        .iter()
+        .any(|s| s.trim().is_empty())
+    {
+        bail!("compose.catme_sections must not contain empty headings");
+    }
+    let mut seen = Vec::new();
+    for block in &config.compose.layout {
+        if !LAYOUT_BLOCKS.contains(&block.as_str()) {
+            bail!(
+                "compose.layout: unknown block \"{block}\" (expected one of: {})",
+                LAYOUT_BLOCKS.join(", ")
+            );
+        }
+        if seen.contains(&block) {
+            bail!("compose.layout: block \"{block}\" appears more than once");
+        }
+        seen.push(block);
+    }
+    for (key, value) in [
+        ("paths.context_mappings", &config.paths.context_mappings),
+        ("paths.issues", &config.paths.issues),
+    ] {
+        let path = Path::new(value);
+        if value.is_empty()
+            || path.is_absolute()
+            || path.components().any(|c| c == Component::ParentDir)
+        {
+            bail!("{key} must be a non-empty path inside .llmd/ (got \"{value}\")");
+        }
+    }
+    if config.tokenizer.mode == TokenizerMode::Bpe
+        && config.tokenizer.vocab.as_deref().is_none_or(str::is_empty)
+    {
+        bail!("tokenizer.vocab must name a vocabulary file when tokenizer.mode is \"bpe\"");
+    }
+    if config.watch.debounce_ms > 60_000 {
+        bail!(
+            "watch.debounce_ms must be at most 60000 (got {})",
+            config.watch.debounce_ms
+        );
+    }
+    if let Some(i) = config
+        .watch
+        .generators
+        .iter()
+        .position(|g| g.command.trim().is_empty())
+    {
+        bail!("watch.generators[{i}].command must not be empty");
+    }
+    for (name, pack) in &config.packs {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!("packs: invalid pack name \"{name}\" (use letters, digits, '-' and '_')");
+        }
+        match (&pack.path, &pack.git) {
+            (Some(_), None) if pack.rev.is_some() => {
+                bail!("packs.{name}: rev only applies to git packs")
+            }
+            (Some(_), None) | (None, Some(_)) => {}
+            _ => bail!("packs.{name}: set exactly one of path and git"),
+        }
+    }
+    Ok(())
+}
+
+/// JSON Schema for `llmd.json`, for editor completion and validation.
+pub fn schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "llmd project configuration (.llmd/llmd.json)",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "index": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "depth": {
+                        "description": "Deepest heading level to index (H2 is always the shallowest)",
+                        "type": "integer", "minimum": 2, "maximum": 4, "default": 3
+                    },
+                    "include_imported": {
+                        "description": "Index files in imported/",
+                        "type": "boolean", "default": false
+                    },
+                    "exclude": {
+                        "description": "Globs, relative to .llmd/, of files to leave out of the index",
+                        "type": "array", "items": { "type": "string" }, "default": []
+                    }
+                }
+            },
+            "compose": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "catme_sections": {
+                        "description": "catme.md sections (heading substrings) quoted in the Project Overview",
+                        "type": "array", "items": { "type": "string", "minLength": 1 },
+                        "default": ["Project Summary", "Technology Stack", "Build"]
+                    },
+                    "layout": {
+                        "description": "Order of the document blocks after the task header",
+                        "type": "array", "uniqueItems": true,
+                        "items": { "enum": LAYOUT_BLOCKS },
+                        "default": LAYOUT_BLOCKS
+                    }
+                }
+            },
+            "paths": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "context_mappings": {
+                        "description": "Label-to-topics mapping used by `llmd compose --issue`",
+                        "type": "string", "default": "context-mappings.json"
+                    },
+                    "issues": {
+                        "description": "Issue tracker directory",
+                        "type": "string", "default": "issues"
+                    }
+                }
+            },
+            "packs": {
+                "description": "External read-only .llmd/ sources, by name",
+                "type": "object",
+                "propertyNames": { "pattern": "^[A-Za-z0-9_-]+$" },
+                "additionalProperties": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "path": {
+                            "description": "Local directory (absolute or relative to the project root), read in place",
+                            "type": "string"
+                        },
+                        "git": {
+                            "description": "Git URL, vendored into .llmd/packs/<name>/ by `llmd pack add/update`",
+                            "type": "string"
+                        },
+                        "rev": {
+                            "description": "Branch, tag or commit to check out",
+                            "type": "string"
+                        }
+                    },
+                    "oneOf": [{ "required": ["path"] }, { "required": ["git"] }]
+                }
+            },
+            "watch": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "mdbook": {
+                        "description": "Keep the mdbook tree in .llmd/.mdbook/ (including the roadmap) up to date",
+                        "type": "boolean", "default": true
+                    },
+                    "source_paths": {
+                        "description": "Also watch the source paths listed in catme.md's Context Map",
+                        "type": "boolean", "default": false
+                    },
+                    "debounce_ms": {
+                        "description": "Quiet period after the last change before regenerating",
+                        "type": "integer", "minimum": 0, "maximum": 60000, "default": 500
+                    },
+                    "generators": {
+                        "description": "Shell commands to run when matching files change",
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "additionalProperties": false,
+                            "required": ["command"],
+                            "properties": {
+                                "command": {
+                                    "description": "Shell command, run from the project root",
+                                    "type": "string", "minLength": 1
+                                },
+                                "on": {
+                                    "description": "Globs, relative to the project root, that trigger the command (empty: any change)",
+                                    "type": "array", "items": { "type": "string" }, "default": []
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "tokenizer": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "mode": {
+                        "description": "heuristic: ~4 bytes per token; bpe: exact counts with the vocab below",
+                        "enum": ["heuristic", "bpe"], "default": "heuristic"
+                    },
+                    "vocab": {
+                        "description": "tiktoken-format vocabulary file, absolute or relative to the project root",
+                        "type": "string"
+                    }
+                }
+            },
+            "usage": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "log": {
+                        "description": "Record read/compose/search runs in .llmd/.usage.jsonl for `llmd usage report`",
+                        "type": "boolean", "default": false
+                    }
+                }
+            },
+            "workspace": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "inherit": {
+                        "description": "Also read .llmd/ directories in enclosing directories (up to the repository root)",
+                        "type": "boolean", "default": true
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_value() {
+        let config = from_value(json!({ "compose": { "layout": ["sections"] } })).unwrap();
+        assert_eq!(config.compose.layout, vec!["sections"]);
+        assert_eq!(config.index.depth, 3);
+
+        assert!(from_value(json!({ "index": { "depth": 5 } })).is_err());
+        assert!(from_value(json!({ "compose": { "layout": ["toc"] } })).is_err());
+        assert!(from_value(json!({ "compose": { "layout": ["overview", "overview"] } })).is_err());
+        assert!(from_value(json!({ "paths": { "issues": "../issues" } })).is_err());
+        assert!(from_value(json!({ "paths": { "issues": "a..b" } })).is_ok());
+        assert!(from_value(json!({ "paths": { "issues": "a/../../b" } })).is_err());
+        assert!(from_value(json!({ "unknown": true })).is_err());
+        assert!(from_value(json!({ "packs": { "sec": { "git": "https://x/y.git" } } })).is_ok());
+        assert!(from_value(json!({ "packs": { "sec": {} } })).is_err());
+        assert!(from_value(json!({ "packs": { "a b": { "path": "../x" } } })).is_err());
+        assert!(from_value(json!({ "tokenizer": { "mode": "bpe" } })).is_err());
+        assert!(
+            from_value(json!({ "tokenizer": { "mode": "bpe", "vocab": "v.tiktoken" } })).is_ok()
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! Scratch project trees for tests.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// A temporary project with a `.llmd/` holding the given files, removed
+/// when dropped. The path is canonical, so it compares equal to paths the
+/// code under test resolves.
+pub struct Fixture(pub PathBuf);
+
+impl Fixture {
+    /// Creates the tree under the system temp dir. `name` keeps fixtures of
+    /// concurrently running tests apart.
+    pub fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let root = std::env::temp_dir().join(format!("llmd-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (rel, content) in files {
+            let path = root.join(".llmd").join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        // Stop the layer walk here.
+        fs::create_dir_all(root.join(".git")).unwrap();
+        Self(root.canonicalize().unwrap())
+    }
+
+    pub fn llmd(&self) -> PathBuf {
+        self.0.join(".llmd")
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,159 @@
+//! Issue comments: the `## Comments` section at the end of an issue body.
+//!
+//! Comments are stored as a YAML list in a ```yaml fence under a
+//! `## Comments` heading, so the file stays readable and older files keep
+//! working. [`split`] takes them out of the body when an issue is parsed and
+//! [`join`] puts them back when it is written.
+
+use anyhow::{Context, Result};
+
+use crate::issues::models::Comment;
+
+const HEADING: &str = "## Comments";
+
+/// Splits a body into its text and its comments. A `## Comments` section
+/// whose fence does not hold a valid comment list is left in the text.
+pub fn split(body: &str) -> (String, Vec<Comment>) {
+    let Some(start) = find_heading(body) else {
+        return (body.to_string(), Vec::new());
+    };
+    let section = &body[start + HEADING.len()..];
+    let Some(open) = section.find("```yaml\n") else {
+        return (body.to_string(), Vec::new());
+    };
+    if !section[..open].trim().is_empty() {
+        return (body.to_string(), Vec::new());
+    }
+    let yaml_start = open + "```yaml\n".len();
+    let rest = &section[yaml_start..];
+    let Some(close) = rest.find("\n```").map(|i| i + 1).or_else(|| {
+        // An empty list: the fence closes right away.
+        rest.starts_with("```").then_some(0)
+    }) else {
+        return (body.to_string(), Vec::new());
+    };
+    let Ok(comments) = serde_yaml_ng::from_str::<Option<Vec<Comment>>>(&rest[..close]) else {
+        return (body.to_string(), Vec::new());
+    };
+    let mut comments = comments.unwrap_or_default();
+    number(&mut comments);
+
+    let after = &rest[close + 3..];
+    let mut text = body[..start].trim_end().to_string();
+    if !after.trim().is_empty() {
+        text.push_str("\n\n");
+        text.push_str(after.trim());
+    }
+    (text, comments)
+}
+
+/// Appends the `## Comments` section for `comments` to `body`, if there are any.
+pub fn join(body: &str, comments: &[Comment]) -> Result<String> {
+    if comments.is_empty() {
+        return Ok(body.to_string());
+    }
+    let yaml = serde_yaml_ng::to_string(comments).context("Cannot serialize comments")?;
+    let mut out = body.trim_end().to_string();
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(&format!("{HEADING}\n\n```yaml\n{yaml}```\n"));
+    Ok(out)
+}
+
+/// The `@handles` mentioned in `text`, without the `@`, in order of appearance.
+pub fn mentions(text: &str) -> Vec<&str> {
+    let mut handles = Vec::new();
+    let mut rest = text;
+    while let Some(at) = rest.find('@') {
+        let preceded_by_word = rest[..at]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '.');
+        let after = &rest[at + 1..];
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(after.len());
+        // Skip e-mail addresses such as ana@example.com.
+        if len > 0 && !preceded_by_word {
+            handles.push(&after[..len]);
+        }
+        rest = &after[len..];
+    }
+    handles
+}
+
+/// Gives comments written before comments had ids the id of their position.
+fn number(comments: &mut [Comment]) {
+    for (i, comment) in comments.iter_mut().enumerate() {
+        if comment.id == 0 {
+            comment.id = i as u32 + 1;
+        }
+    }
+}
+
+/// The offset of the `## Comments` heading line, if the body has one.
+fn find_heading(body: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        if line.trim_end() == HEADING {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        // The format `llmd issue update --add-comment` used to write.
+        let body = "Some text.\n\n## Comments\n\n```yaml\n- author: \"ana\"\n  date: \"2026-10-01T10:00:00Z\"\n  body: \"Looks good @bo\"\n```\n";
+        let (text, comments) = split(body);
+        assert_eq!(text, "Some text.");
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].id, 1);
+        assert_eq!(comments[0].body, "Looks good @bo");
+
+        let (text, none) = split("Text.\n\n## Comments\n\n```yaml\nnot: [a list\n```\n");
+        assert!(none.is_empty());
+        assert!(text.contains("## Comments"));
+    }
+
+    #[test]
+    fn test_join_round_trip() {
+        let comments = vec![
+            Comment {
+                id: 1,
+                author: "ana".to_string(),
+                date: "2026-10-01T10:00:00Z".to_string(),
+                reply_to: None,
+                handoff_to: None,
+                body: "First line\n\n- a list: with colons\n```code```".to_string(),
+            },
+            Comment {
+                id: 2,
+                author: "bo".to_string(),
+                date: "2026-10-02T10:00:00Z".to_string(),
+                reply_to: Some(1),
+                handoff_to: Some("agent-ui".to_string()),
+                body: "Reply".to_string(),
+            },
+        ];
+        let body = join("Text.", &comments).unwrap();
+        assert_eq!(split(&body), ("Text.".to_string(), comments));
+        assert_eq!(join("Text.", &[]).unwrap(), "Text.");
+    }
+
+    #[test]
+    fn test_mentions() {
+        assert_eq!(
+            mentions("@ana, cc @bo-2. Mail ana@example.com"),
+            vec!["ana", "bo-2"]
+        );
+        assert!(mentions("no handles @ here").is_empty());
+    }
+}
@@ -1,10 +1,11 @@
 //! File I/O for the issue tracker.
 
 use std::collections::HashMap;
-use std::fs;
+use std::fs::{self, File, OpenOptions};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use iso8601_timestamp::Timestamp;
 
 use super::frontmatter::{parse_issue, serialize_issue};
@@ -15,11 +16,73 @@ pub fn config_path(issues_dir: &Path) -> PathBuf {
     issues_dir.join("config.json")
 }
 
+/// Path to the lock file inside issues dir.
+pub fn lock_path(issues_dir: &Path) -> PathBuf {
+    issues_dir.join(".lock")
+}
+
+/// Takes the tracker-wide advisory lock, waiting for other llmd processes to
+/// release it. The lock is held until the returned file is dropped.
+///
+/// Commands that read, change and write tracker files (`new`, `update`,
+/// `close`, `claim`, `release`) hold it for the whole sequence, so parallel
+/// agents cannot allocate the same id or overwrite each other's changes.
+pub fn lock(issues_dir: &Path) -> Result<File> {
+    let path = lock_path(issues_dir);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Cannot open {}", path.display()))?;
+    file.lock()
+        .with_context(|| format!("Cannot lock {}", path.display()))?;
+    Ok(file)
+}
+
 /// Returns current timestamp in ISO 8601 format.
 pub fn now_iso() -> String {
     Timestamp::now_utc().to_string()
 }
 
+/// Returns the timestamp `duration` from now in ISO 8601 format. Fails if
+/// that is beyond the largest representable date.
+pub fn iso_after(duration: Duration) -> Result<String> {
+    let after = iso8601_timestamp::Duration::try_from(duration)
+        .ok()
+        .and_then(|d| Timestamp::now_utc().checked_add(d));
+    match after {
+        Some(ts) => Ok(ts.to_string()),
+        None => bail!("the latest supported date is in the year 9999"),
+    }
+}
+
+/// True if `ts` is a valid ISO 8601 timestamp later than now.
+pub fn is_future(ts: &str) -> bool {
+    Timestamp::parse(ts).is_some_and(|t| t > Timestamp::now_utc())
+}
+
+/// Parses a duration such as `45s`, `30m`, `2h` or `1d`.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => bail!("Invalid duration \"{s}\": expected a number followed by s, m, h or d"),
+    };
+    let Ok(number) = number.parse::<u64>() else {
+        bail!("Invalid duration \"{s}\": expected a number followed by s, m, h or d");
+    };
+    let Some(total) = number.checked_mul(seconds) else {
+        bail!("Duration \"{s}\" is too long");
+    };
+    Ok(Duration::from_secs(total))
+}
+
 /// Loads config from .llmd/issues/config.json.
 pub fn load_config(issues_dir: &Path) -> Result<Config> {
     let path = config_path(issues_dir);
@@ -29,19 +92,38 @@ pub fn load_config(issues_dir: &Path) -> Result<Config> {
             path.display()
         )
     })?;
-    serde_json::from_str(&content).context("Invalid config.json")
+    let config: Config = serde_json::from_str(&content).context("Invalid config.json")?;
+    config
+        .workflow
+        .validate()
+        .with_context(|| format!("Invalid workflow in {}", path.display()))?;
+    Ok(config)
 }
 
 /// Saves config to .llmd/issues/config.json.
 pub fn save_config(issues_dir: &Path, config: &Config) -> Result<()> {
     let path = config_path(issues_dir);
     let content = serde_json::to_string_pretty(config).context("Failed to serialize config")?;
-    fs::write(&path, content).with_context(|| format!("Cannot write {}", path.display()))
+    write_atomic(&path, &content)
 }
 
-/// Loads all issues from .llmd/issues/.
-pub fn load_all_issues(issues_dir: &Path) -> Result<HashMap<u32, Issue>> {
-    let mut map = HashMap::new();
+/// Writes `content` to a temporary file next to `path` and renames it over
+/// `path`, so readers never see a half-written file.
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let tmp = path.with_file_name(format!(".{name}.{}.tmp", std::process::id()));
+    fs::write(&tmp, content).with_context(|| format!("Cannot write {}", tmp.display()))?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("Cannot write {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Lists the issue files in .llmd/issues/ with the id in their name, sorted
+/// by file name. After a git merge, two files may carry the same id.
+pub fn issue_files(issues_dir: &Path) -> Result<Vec<(u32, PathBuf)>> {
+    let mut files = Vec::new();
     let entries = fs::read_dir(issues_dir).context("Cannot read issues directory")?;
 
     for entry in entries.flatten() {
@@ -49,14 +131,22 @@ pub fn load_all_issues(issues_dir: &Path) -> Result<HashMap<u32, Issue>> {
         if path.extension().and_then(|e| e.to_str()) != Some("md") {
             continue;
         }
-        if path.file_name().and_then(|n| n.to_str()) == Some("config.json") {
-            continue;
-        }
         let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
         let id_str = stem.split('-').next().unwrap_or("");
         let Ok(id) = id_str.parse::<u32>() else {
             continue;
         };
+        files.push((id, path));
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Loads all issues from .llmd/issues/. Of several files with the same id,
+/// the last by file name wins; `llmd issue renumber` repairs that.
+pub fn load_all_issues(issues_dir: &Path) -> Result<HashMap<u32, Issue>> {
+    let mut map = HashMap::new();
+    for (id, path) in issue_files(issues_dir)? {
         let content =
             fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
         if let Some(issue) = parse_issue(&content, id) {
@@ -66,8 +156,11 @@ pub fn load_all_issues(issues_dir: &Path) -> Result<HashMap<u32, Issue>> {
     Ok(map)
 }
 
-/// Resolves id or slug to an issue file path.
+/// Resolves id, alias or slug to an issue file path. An id match wins over an
+/// alias, and both over a slug match, so `5` finds `005-x.md` even if
+/// `013-part-5.md` exists.
 pub fn resolve_issue_path(issues_dir: &Path, id_or_slug: &str) -> Option<PathBuf> {
+    let mut by_slug = None;
     let entries = fs::read_dir(issues_dir).ok()?;
     for entry in entries.flatten() {
         let path = entry.path();
@@ -80,17 +173,66 @@ pub fn resolve_issue_path(issues_dir: &Path, id_or_slug: &str) -> Option<PathBuf
         {
             return Some(path);
         }
-        if stem.ends_with(&format!("-{id_or_slug}")) || stem == id_or_slug {
+        if by_slug.is_none() && (stem.ends_with(&format!("-{id_or_slug}")) || stem == id_or_slug) {
+            by_slug = Some(path);
+        }
+    }
+    if let Ok(alias) = id_or_slug.parse::<u32>() {
+        let files = issue_files(issues_dir).ok()?;
+        let by_alias = files.into_iter().find(|(id, path)| {
+            fs::read_to_string(path)
+                .ok()
+                .and_then(|content| parse_issue(&content, *id))
+                .is_some_and(|issue| issue.alias == Some(alias))
+        });
+        if let Some((_, path)) = by_alias {
             return Some(path);
         }
     }
-    None
+    by_slug
 }
 
 /// Writes an issue to disk.
 pub fn write_issue(issues_dir: &Path, issue: &Issue) -> Result<()> {
     let filename = format!("{:03}-{}.md", issue.id, issue.slug);
     let path = issues_dir.join(&filename);
-    let content = serialize_issue(issue);
-    fs::write(&path, content).with_context(|| format!("Cannot write {}", path.display()))
+    let content = serialize_issue(issue)?;
+    write_atomic(&path, &content)
+}
+
+/// Fails if issue `id` on disk no longer has the `updated_at` it had when it
+/// was read, i.e. someone else wrote it in the meantime.
+pub fn ensure_unchanged(issues_dir: &Path, id: u32, updated_at: &str) -> Result<()> {
+    let path = resolve_issue_path(issues_dir, &id.to_string())
+        .with_context(|| format!("Issue #{id} was removed while updating it"))?;
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let current = parse_issue(&content, id).map(|i| i.updated_at);
+    if current.as_deref() != Some(updated_at) {
+        bail!(
+            "Issue #{id} was changed by someone else (updated_at is now {}, expected {updated_at}). Re-read it and try again.",
+            current.as_deref().unwrap_or("missing")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86_400));
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("m").is_err());
+        let long = parse_duration("99999999999999999d")
+            .unwrap_err()
+            .to_string();
+        assert!(long.contains("too long"), "{long}");
+        assert!(is_future(&iso_after(Duration::from_secs(60)).unwrap()));
+        assert!(iso_after(parse_duration("99999999999999d").unwrap()).is_err());
+        assert!(!is_future("2020-01-01T00:00:00.000Z"));
+    }
 }
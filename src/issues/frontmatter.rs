@@ -1,194 +1,120 @@
-//! Hand-rolled YAML frontmatter parser for issue files.
+//! YAML frontmatter of issue files.
 //!
-//! Handles scalars, flat arrays, and label objects. No serde_yaml dependency.
+//! The frontmatter is parsed into an ordered YAML mapping. Known keys fill in
+//! the typed [`Issue`] fields; the whole mapping is kept on the issue so that
+//! [`serialize_issue`] can write unknown keys back, in their original order,
+//! after updating the known ones in place.
 
-use std::collections::HashMap;
+use anyhow::{Context, Result};
+use serde_yaml_ng::{Mapping, Value};
 
+use crate::issues::comments;
 use crate::issues::models::{Issue, Label};
 
-/// Parses an issue file (--- ... ---\n\nbody) into an Issue.
+/// Parses an issue file (--- ... ---\n\nbody) into an Issue. Returns `None`
+/// if the file has no frontmatter or the frontmatter is not a YAML mapping.
 pub fn parse_issue(content: &str, id: u32) -> Option<Issue> {
     let (fm, body) = split_frontmatter(content)?;
-    let mut map = parse_frontmatter_map(fm);
-
-    let title = map
-        .remove("title")
-        .unwrap_or_else(|| "Untitled".to_string());
-    let slug = map.remove("slug").unwrap_or_else(|| slugify(&title));
-    let issue_type = map.remove("type").unwrap_or_else(|| "task".to_string());
-    let status = map.remove("status").unwrap_or_else(|| "open".to_string());
-    let priority = map
-        .remove("priority")
-        .unwrap_or_else(|| "medium".to_string());
-    let assignee = map
-        .remove("assignee")
-        .filter(|s| !s.is_empty() && s != "null");
-    let milestone = map.remove("milestone").filter(|s| !s.is_empty());
-    let parent = map.remove("parent").and_then(|s| s.parse().ok());
-    let created_at = map.remove("created_at").unwrap_or_default();
-    let updated_at = map
-        .remove("updated_at")
-        .unwrap_or_else(|| created_at.clone());
-    let due = map.remove("due").filter(|s| !s.is_empty());
-    let points = map.remove("points").and_then(|s| s.parse().ok());
+    let frontmatter: Mapping = if fm.trim().is_empty() {
+        Mapping::new()
+    } else {
+        serde_yaml_ng::from_str(fm).ok()?
+    };
 
-    let labels = parse_labels(fm);
-    let dependencies = parse_array_field(fm, "dependencies");
-    let epic_children = parse_array_field(fm, "epic_children");
+    let text = |key: &str| scalar(frontmatter.get(key)).filter(|s| !s.is_empty());
+    let title = text("title").unwrap_or_else(|| "Untitled".to_string());
+    let slug = text("slug").unwrap_or_else(|| slugify(&title));
+    let created_at = text("created_at").unwrap_or_default();
+    let updated_at = text("updated_at").unwrap_or_else(|| created_at.clone());
+    let (body, comments) = comments::split(body.trim());
 
     Some(Issue {
         id,
-        title,
+        alias: number(frontmatter.get("alias")),
         slug,
-        issue_type,
-        status,
-        priority,
-        labels,
-        assignee,
-        milestone,
-        parent,
-        dependencies,
-        epic_children,
-        points,
-        due,
+        issue_type: text("type").unwrap_or_else(|| "task".to_string()),
+        status: text("status").unwrap_or_else(|| "open".to_string()),
+        priority: text("priority").unwrap_or_else(|| "medium".to_string()),
+        labels: labels(frontmatter.get("labels")),
+        assignee: text("assignee"),
+        handover_to: text("handover_to"),
+        milestone: text("milestone"),
+        parent: number(frontmatter.get("parent")),
+        dependencies: numbers(frontmatter.get("dependencies")),
+        epic_children: numbers(frontmatter.get("epic_children")),
+        points: number(frontmatter.get("points")),
+        due: text("due"),
+        title,
         created_at,
         updated_at,
+        resolution: text("resolution"),
+        closed_at: text("closed_at"),
+        lease_expires_at: text("lease_expires_at"),
         body: body.trim().to_string(),
+        comments,
+        frontmatter,
     })
 }
 
+/// Splits `---\n<frontmatter>\n---\n<body>` at the first line that is exactly `---`.
 fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
-    let content = content.strip_prefix("---\n")?;
-    let (fm, rest) = content.split_once("\n---")?;
-    let body = rest.strip_prefix('\n').unwrap_or(rest);
-    Some((fm.trim(), body))
-}
-
-fn parse_frontmatter_map(fm: &str) -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    let mut key = String::new();
-    let mut value = String::new();
-    let mut in_key = true;
-
-    for line in fm.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
-        if line.starts_with(|c: char| c.is_alphabetic() || c == '_') {
-            if !key.is_empty() {
-                map.insert(
-                    key.clone(),
-                    value
-                        .trim()
-                        .trim_matches('"')
-                        .trim_matches('\'')
-                        .to_string(),
-                );
-            }
-            if let Some((k, v)) = line.split_once(':') {
-                key = k.trim().to_string();
-                value = v.trim().trim_matches('"').trim_matches('\'').to_string();
-                in_key = false;
-            }
-        } else if line.starts_with('-') && !key.is_empty() {
-            // Array element or nested object - skip for simple scalar map
-            continue;
-        } else if !in_key && !value.is_empty() && line.starts_with(' ') {
-            value.push(' ');
-            value.push_str(line.trim());
+    let rest = content.strip_prefix("---\n")?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
         }
+        offset += line.len();
     }
-    if !key.is_empty() {
-        map.insert(
-            key,
-            value
-                .trim()
-                .trim_matches('"')
-                .trim_matches('\'')
-                .to_string(),
-        );
-    }
-    map
+    None
 }
 
-fn parse_labels(fm: &str) -> Vec<Label> {
-    let mut labels = Vec::new();
-    let mut in_labels = false;
-    let mut base_indent = 0usize;
-
-    let lines: Vec<&str> = fm.lines().collect();
-    let mut i = 0usize;
+/// A scalar as text; `None` for null, sequences and mappings.
+fn scalar(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Tagged(tagged) => scalar(Some(&tagged.value)),
+        _ => None,
+    }
+}
 
-    while i < lines.len() {
-        let line = lines[i];
-        if line.trim_start().starts_with("labels:") {
-            let rest = line.split_once(':').map(|(_, r)| r.trim()).unwrap_or("");
-            if let Some(bracket) = rest.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
-                for part in bracket.split(',') {
-                    let name = part.trim().trim_matches('"').trim_matches('\'').to_string();
-                    if !name.is_empty() {
-                        labels.push(Label { name, color: None });
-                    }
-                }
-            }
-            in_labels = true;
-            base_indent = line.len() - line.trim_start().len();
-            i += 1;
-            continue;
-        }
-        if in_labels {
-            let trimmed = line.trim_start();
-            let line_indent = line.len() - trimmed.len();
-            if line_indent <= base_indent && !trimmed.is_empty() && !trimmed.starts_with('-') {
-                in_labels = false;
-                i += 1;
-                continue;
-            }
-            if trimmed.starts_with("- name:") || trimmed.starts_with("-  name:") {
-                let name = trimmed
-                    .split("name:")
-                    .nth(1)
-                    .and_then(|s| s.split_whitespace().next())
-                    .map(|s| s.trim_matches('"').trim_matches('\'').to_string())
-                    .unwrap_or_default();
-                let mut color = None;
-                if i + 1 < lines.len() {
-                    let next = lines[i + 1];
-                    if next.trim_start().starts_with("color:") {
-                        color = next
-                            .split("color:")
-                            .nth(1)
-                            .and_then(|s| s.split_whitespace().next())
-                            .map(|s| s.trim_matches('"').to_string());
-                        i += 1;
-                    }
-                }
-                if !name.is_empty() {
-                    labels.push(Label { name, color });
-                }
-            }
-        }
-        i += 1;
+fn number(value: Option<&Value>) -> Option<u32> {
+    match value? {
+        Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
     }
-    labels
 }
 
-fn parse_array_field(fm: &str, field: &str) -> Vec<u32> {
-    let mut result = Vec::new();
-    for line in fm.lines() {
-        if line.trim_start().starts_with(&format!("{field}:")) {
-            let rest = line.split_once(':').map(|(_, r)| r.trim()).unwrap_or("");
-            if let Some(bracket) = rest.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
-                for part in bracket.split(',') {
-                    if let Ok(n) = part.trim().parse::<u32>() {
-                        result.push(n);
-                    }
-                }
-            }
-            break;
-        }
+/// A list of ids, written as a flow or block sequence (or a single id).
+fn numbers(value: Option<&Value>) -> Vec<u32> {
+    match value {
+        Some(Value::Sequence(items)) => items.iter().filter_map(|v| number(Some(v))).collect(),
+        other => number(other).into_iter().collect(),
     }
-    result
+}
+
+/// Labels, written as plain names or as `{name, color}` mappings.
+fn labels(value: Option<&Value>) -> Vec<Label> {
+    let Some(Value::Sequence(items)) = value else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Value::Mapping(m) => Some(Label {
+                name: scalar(m.get("name"))?,
+                color: scalar(m.get("color")),
+            }),
+            other => Some(Label {
+                name: scalar(Some(other))?,
+                color: None,
+            }),
+        })
+        .filter(|l| !l.name.is_empty())
+        .collect()
 }
 
 fn slugify(s: &str) -> String {
@@ -207,70 +133,339 @@ fn slugify(s: &str) -> String {
         .join("-")
 }
 
-/// Serializes an Issue to frontmatter + body for writing.
-pub fn serialize_issue(issue: &Issue) -> String {
-    let mut out = String::from("---\n");
-    out.push_str(&format!("id: {}\n", issue.id));
-    out.push_str(&format!("title: \"{}\"\n", escape_yaml_str(&issue.title)));
-    out.push_str(&format!("slug: \"{}\"\n", issue.slug));
-    out.push_str(&format!("type: {}\n", issue.issue_type));
-    out.push_str(&format!("status: {}\n", issue.status));
-    out.push_str(&format!("priority: {}\n", issue.priority));
+/// Serializes an Issue to frontmatter + body for writing. Known keys keep
+/// their position in `issue.frontmatter` (new ones are appended in the order
+/// below), optional keys without a value are dropped, and unknown keys are
+/// written back unchanged.
+pub fn serialize_issue(issue: &Issue) -> Result<String> {
+    let optional = |value: &Option<String>| value.clone().map(Value::from);
+    let ids = |ids: &[u32]| Value::from(ids.to_vec());
+    let labels: Vec<Value> = issue
+        .labels
+        .iter()
+        .map(|l| {
+            let mut label = Mapping::new();
+            label.insert("name".into(), l.name.clone().into());
+            if let Some(color) = &l.color {
+                label.insert("color".into(), color.clone().into());
+            }
+            Value::Mapping(label)
+        })
+        .collect();
 
-    if issue.labels.is_empty() {
-        out.push_str("labels: []\n");
-    } else {
-        out.push_str("labels:\n");
-        for l in &issue.labels {
-            if let Some(ref c) = l.color {
-                out.push_str(&format!(
-                    "  - name: \"{}\"\n    color: \"{}\"\n",
-                    escape_yaml_str(&l.name),
-                    c
-                ));
-            } else {
-                out.push_str(&format!("  - name: \"{}\"\n", escape_yaml_str(&l.name)));
+    let known = [
+        ("id", Some(issue.id.into())),
+        ("alias", issue.alias.map(Value::from)),
+        ("title", Some(issue.title.clone().into())),
+        ("slug", Some(issue.slug.clone().into())),
+        ("type", Some(issue.issue_type.clone().into())),
+        ("status", Some(issue.status.clone().into())),
+        ("priority", Some(issue.priority.clone().into())),
+        ("labels", Some(labels.into())),
+        (
+            "assignee",
+            Some(optional(&issue.assignee).unwrap_or(Value::Null)),
+        ),
+        ("handover_to", optional(&issue.handover_to)),
+        ("milestone", optional(&issue.milestone)),
+        (
+            "parent",
+            Some(issue.parent.map_or(Value::Null, Value::from)),
+        ),
+        ("dependencies", Some(ids(&issue.dependencies))),
+        (
+            "epic_children",
+            (!issue.epic_children.is_empty()).then(|| ids(&issue.epic_children)),
+        ),
+        ("points", issue.points.map(Value::from)),
+        ("due", optional(&issue.due)),
+        ("created_at", Some(issue.created_at.clone().into())),
+        ("updated_at", Some(issue.updated_at.clone().into())),
+        ("resolution", optional(&issue.resolution)),
+        ("closed_at", optional(&issue.closed_at)),
+        ("lease_expires_at", optional(&issue.lease_expires_at)),
+    ];
+
+    let mut frontmatter = issue.frontmatter.clone();
+    for (key, value) in known {
+        match value {
+            Some(value) => {
+                frontmatter.insert(key.into(), value);
+            }
+            None => {
+                frontmatter.shift_remove(key);
             }
         }
     }
 
-    if let Some(ref a) = issue.assignee {
-        out.push_str(&format!("assignee: \"{}\"\n", escape_yaml_str(a)));
-    } else {
-        out.push_str("assignee: null\n");
+    let yaml = serde_yaml_ng::to_string(&frontmatter)
+        .with_context(|| format!("Cannot serialize the frontmatter of issue #{}", issue.id))?;
+    let body = comments::join(&issue.body, &issue.comments)?;
+    let mut out = format!("---\n{yaml}---\n\n");
+    out.push_str(&body);
+    if !body.is_empty() && !body.ends_with('\n') {
+        out.push('\n');
     }
-    if let Some(ref m) = issue.milestone {
-        out.push_str(&format!("milestone: \"{}\"\n", escape_yaml_str(m)));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issues::models::Comment;
+    use proptest::prelude::*;
+
+    fn issue() -> Issue {
+        Issue {
+            id: 7,
+            alias: None,
+            title: "Fix login".to_string(),
+            slug: "fix-login".to_string(),
+            issue_type: "bug".to_string(),
+            status: "open".to_string(),
+            priority: "high".to_string(),
+            labels: vec![Label {
+                name: "api".to_string(),
+                color: Some("#ff0000".to_string()),
+            }],
+            assignee: None,
+            handover_to: None,
+            milestone: None,
+            parent: Some(3),
+            dependencies: vec![1, 2],
+            epic_children: Vec::new(),
+            points: None,
+            due: None,
+            created_at: "2026-10-18T10:00:00.000Z".to_string(),
+            updated_at: "2026-10-18T10:00:00.000Z".to_string(),
+            resolution: None,
+            closed_at: None,
+            lease_expires_at: None,
+            body: "Body.".to_string(),
+            comments: Vec::new(),
+            frontmatter: Mapping::new(),
+        }
     }
-    if let Some(p) = issue.parent {
-        out.push_str(&format!("parent: {}\n", p));
-    } else {
-        out.push_str("parent: null\n");
+
+    #[test]
+    fn test_serialize_issue() {
+        let out = serialize_issue(&issue()).unwrap();
+        assert_eq!(
+            out,
+            "---\nid: 7\ntitle: Fix login\nslug: fix-login\ntype: bug\nstatus: open\npriority: high\nlabels:\n- name: api\n  color: '#ff0000'\nassignee: null\nparent: 3\ndependencies:\n- 1\n- 2\ncreated_at: 2026-10-18T10:00:00.000Z\nupdated_at: 2026-10-18T10:00:00.000Z\n---\n\nBody.\n"
+        );
     }
-    if issue.dependencies.is_empty() {
-        out.push_str("dependencies: []\n");
-    } else {
-        out.push_str(&format!("dependencies: {:?}\n", issue.dependencies));
+
+    #[test]
+    fn test_parse_issue() {
+        let content = "---\nid: 7\ntitle: >-\n  Fix the\n  login page\nestimate:\n  low: 1\n  high: 3\nstatus: in-progress\nlabels: [api, {name: ui, color: blue}]\ndependencies:\n  - 1\n  - 2\nreviewers: [ana, bo]\n---\n\nBody text.\n";
+        let issue = parse_issue(content, 7).unwrap();
+        assert_eq!(issue.title, "Fix the login page");
+        assert_eq!(issue.slug, "fix-the-login-page");
+        assert_eq!(issue.status, "in-progress");
+        assert_eq!(issue.labels[0].name, "api");
+        assert_eq!(issue.labels[1].color.as_deref(), Some("blue"));
+        assert_eq!(issue.dependencies, vec![1, 2]);
+        assert_eq!(issue.body, "Body text.");
+        assert!(parse_issue("no frontmatter", 1).is_none());
     }
-    if !issue.epic_children.is_empty() {
-        out.push_str(&format!("epic_children: {:?}\n", issue.epic_children));
+
+    #[test]
+    fn test_serialize_issue_keeps_unknown_keys_in_place() {
+        let content = "---\nid: 7\nestimate:\n  low: 1\n  high: 3\ntitle: Old\nreviewers:\n- ana\n---\n\nBody.\n";
+        let mut issue = parse_issue(content, 7).unwrap();
+        issue.title = "New".to_string();
+        let out = serialize_issue(&issue).unwrap();
+        let keys: Vec<&str> = out
+            .lines()
+            .filter(|l| !l.starts_with([' ', '-']))
+            .filter_map(|l| l.split_once(':').map(|(k, _)| k))
+            .collect();
+        assert_eq!(
+            &keys[..4],
+            ["id", "estimate", "title", "reviewers"],
+            "{out}"
+        );
+        assert!(out.contains("title: New\n"));
+        assert!(out.contains("  high: 3\n"));
+        assert!(out.ends_with("---\n\nBody.\n"));
     }
-    if let Some(pt) = issue.points {
-        out.push_str(&format!("points: {}\n", pt));
+
+    /// Text with the characters YAML is picky about: quotes, colons, `#`,
+    /// leading indicators, newlines and non-ASCII.
+    fn text() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9 :#'\"\\-\\[\\]{}|>*&!%@`,?\\\\\\né✓]{0,30}"
     }
-    if let Some(ref d) = issue.due {
-        out.push_str(&format!("due: \"{}\"\n", d));
+
+    fn word() -> impl Strategy<Value = String> {
+        "[a-z0-9][a-z0-9-]{0,10}"
     }
-    out.push_str(&format!("created_at: \"{}\"\n", issue.created_at));
-    out.push_str(&format!("updated_at: \"{}\"\n", issue.updated_at));
-    out.push_str("---\n\n");
-    out.push_str(&issue.body);
-    if !issue.body.is_empty() && !issue.body.ends_with('\n') {
-        out.push('\n');
+
+    fn label() -> impl Strategy<Value = Label> {
+        (text(), proptest::option::of(word())).prop_filter_map("empty name", |(name, color)| {
+            (!name.is_empty()).then_some(Label { name, color })
+        })
+    }
+
+    fn yaml_value() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::from),
+            any::<i64>().prop_map(Value::from),
+            text().prop_map(Value::from),
+        ];
+        leaf.prop_recursive(3, 16, 4, |inner| {
+            prop_oneof![
+                proptest::collection::vec(inner.clone(), 0..4).prop_map(Value::from),
+                proptest::collection::vec(("[a-z]{1,6}", inner), 0..4).prop_map(|entries| {
+                    Value::Mapping(
+                        entries
+                            .into_iter()
+                            .map(|(k, v)| (Value::from(k), v))
+                            .collect(),
+                    )
+                }),
+            ]
+        })
+    }
+
+    /// Extra frontmatter keys llmd does not know about.
+    fn unknown_keys() -> impl Strategy<Value = Mapping> {
+        proptest::collection::vec(("x_[a-z]{1,8}", yaml_value()), 0..4)
+            .prop_map(|entries| entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
     }
-    out
-}
 
-fn escape_yaml_str(s: &str) -> String {
-    s.replace('\\', "\\\\").replace('"', "\\\"")
+    fn comment() -> impl Strategy<Value = Comment> {
+        (
+            1..50u32,
+            word(),
+            word(),
+            proptest::option::of(1..50u32),
+            proptest::option::of(word()),
+            text(),
+        )
+            .prop_map(|(id, author, date, reply_to, handoff_to, body)| Comment {
+                id,
+                author,
+                date,
+                reply_to,
+                handoff_to,
+                body,
+            })
+    }
+
+    fn arb_issue() -> impl Strategy<Value = Issue> {
+        let text_or_empty = |s: String| (!s.trim().is_empty()).then_some(s);
+        (
+            (
+                1..10_000u32,
+                proptest::option::of(1..10_000u32),
+                text(),
+                word(),
+                word(),
+                word(),
+                word(),
+                proptest::collection::vec(label(), 0..4),
+            ),
+            (
+                text().prop_map(text_or_empty),
+                proptest::option::of(word()),
+                text().prop_map(text_or_empty),
+                proptest::option::of(1..10_000u32),
+                proptest::collection::vec(1..10_000u32, 0..4),
+                proptest::collection::vec(1..10_000u32, 0..4),
+                proptest::option::of(0..100u32),
+                proptest::option::of("2026-[01][0-9]-[0-3][0-9]"),
+            ),
+            (
+                word(),
+                word(),
+                proptest::option::of(word()),
+                proptest::option::of(word()),
+                proptest::option::of(word()),
+                text(),
+                proptest::collection::vec(comment(), 0..3),
+                unknown_keys(),
+            ),
+        )
+            .prop_map(
+                |(
+                    (id, alias, title, slug, issue_type, status, priority, labels),
+                    (
+                        assignee,
+                        handover_to,
+                        milestone,
+                        parent,
+                        dependencies,
+                        epic_children,
+                        points,
+                        due,
+                    ),
+                    (
+                        created_at,
+                        updated_at,
+                        resolution,
+                        closed_at,
+                        lease_expires_at,
+                        body,
+                        comments,
+                        frontmatter,
+                    ),
+                )| Issue {
+                    id,
+                    alias,
+                    title: if title.is_empty() {
+                        "Untitled".to_string()
+                    } else {
+                        title
+                    },
+                    slug,
+                    issue_type,
+                    status,
+                    priority,
+                    labels,
+                    assignee,
+                    handover_to,
+                    milestone,
+                    parent,
+                    dependencies,
+                    epic_children,
+                    points,
+                    due,
+                    created_at,
+                    updated_at,
+                    resolution,
+                    closed_at,
+                    lease_expires_at,
+                    body: body.trim().to_string(),
+                    comments,
+                    frontmatter,
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(issue in arb_issue()) {
+            let out = serialize_issue(&issue).unwrap();
+            let mut back = parse_issue(&out, issue.id).unwrap();
+            // Unknown keys come back in their original order, after the known ones.
+            let unknown: Mapping = back
+                .frontmatter
+                .iter()
+                .filter(|(k, _)| k.as_str().is_some_and(|k| k.starts_with("x_")))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            prop_assert_eq!(&unknown, &issue.frontmatter);
+            back.frontmatter = issue.frontmatter.clone();
+            prop_assert_eq!(back, issue);
+        }
+
+        #[test]
+        fn test_serialize_is_stable(issue in arb_issue()) {
+            let out = serialize_issue(&issue).unwrap();
+            let again = serialize_issue(&parse_issue(&out, issue.id).unwrap()).unwrap();
+            prop_assert_eq!(again, out);
+        }
+    }
 }
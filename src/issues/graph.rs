@@ -1,88 +1,308 @@
 //! Dependency graph operations: ready tasks, cycle detection, epic tree.
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use anyhow::{Result, bail};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
-use crate::issues::models::Issue;
+use crate::issues::models::{Issue, Resolution};
+use crate::issues::workflow::Workflow;
 
-/// Returns open issues that have no unresolved dependencies.
+/// Returns issues in a non-terminal status that have no unresolved
+/// dependencies and are not claimed, most urgent first.
 /// Excludes epics (they are containers, not actionable).
-pub fn ready_tasks(issues: &HashMap<u32, Issue>, exclude_epics: bool) -> Vec<&Issue> {
-    let closed: HashSet<u32> = issues
-        .values()
-        .filter(|i| i.status == "closed")
-        .map(|i| i.id)
-        .collect();
-
+pub fn ready_tasks<'a>(
+    issues: &'a HashMap<u32, Issue>,
+    workflow: &Workflow,
+    exclude_epics: bool,
+) -> Vec<&'a Issue> {
     let mut result: Vec<&Issue> = issues
         .values()
         .filter(|i| {
-            i.status != "closed"
+            !workflow.is_terminal(&i.status)
+                && !i.has_live_lease()
                 && (!exclude_epics || i.issue_type != "epic")
-                && i.dependencies.iter().all(|dep| closed.contains(dep))
+                && i.dependencies
+                    .iter()
+                    .all(|&dep| is_resolved(issues, workflow, dep))
         })
         .collect();
 
-    result.sort_by(|a, b| {
-        let pri = |p: &str| match p {
-            "high" => 0,
-            "medium" => 1,
-            "low" => 2,
-            _ => 1,
-        };
-        pri(&a.priority)
-            .cmp(&pri(&b.priority))
-            .then_with(|| a.id.cmp(&b.id))
-    });
+    result.sort_by_key(|i| (workflow.priority_rank(&i.priority), i.id));
     result
 }
 
-/// Detects cycles in the dependency + parent graph.
-#[allow(dead_code)]
-pub fn has_cycle(issues: &HashMap<u32, Issue>) -> bool {
-    let mut visited = HashSet::new();
-    let mut stack = HashSet::new();
+/// True if the issue is in a terminal status and nothing more will happen to
+/// it: it was done or won't be fixed, or it is a duplicate of an issue that
+/// is itself resolved. Missing issues are unresolved.
+pub fn is_resolved(issues: &HashMap<u32, Issue>, workflow: &Workflow, id: u32) -> bool {
+    let mut seen = HashSet::new();
+    let mut current = id;
+    loop {
+        let Some(issue) = issues.get(&current) else {
+            return false;
+        };
+        if !workflow.is_terminal(&issue.status) {
+            return false;
+        }
+        match issue.resolution() {
+            Some(Resolution::Duplicate(original)) => {
+                if !seen.insert(current) {
+                    return false;
+                }
+                current = original;
+            }
+            _ => return true,
+        }
+    }
+}
+
+/// The two kinds of link between issues that must not form cycles. They are
+/// checked separately: an epic may depend on its own children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Link {
+    /// `dependencies`: a cycle means `ready` never offers its issues.
+    Dependency,
+    /// `parent`: a cycle means an issue is its own ancestor.
+    Parent,
+}
+
+impl Link {
+    const ALL: [Link; 2] = [Link::Dependency, Link::Parent];
+
+    /// Outgoing edges of this kind: the issues `issue` depends on, or its parent.
+    fn edges(self, issue: &Issue) -> Vec<u32> {
+        match self {
+            Link::Dependency => issue.dependencies.clone(),
+            Link::Parent => issue.parent.into_iter().collect(),
+        }
+    }
 
-    for &id in issues.keys() {
-        if !visited.contains(&id) && cycle_dfs(id, issues, &mut visited, &mut stack) {
-            return true;
+    /// `dependency cycle` or `parent cycle`, for messages.
+    fn cycle_name(self) -> &'static str {
+        match self {
+            Link::Dependency => "dependency cycle",
+            Link::Parent => "parent cycle",
         }
     }
-    false
 }
 
-#[allow(dead_code)]
+/// Formats a cycle path as `#3 → #5 → #3`.
+pub fn format_cycle(cycle: &[u32]) -> String {
+    cycle
+        .iter()
+        .map(|id| format!("#{id}"))
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
+
+/// Returns the shortest cycle through `id` along links of kind `link`, as a
+/// path from `id` back to itself.
+pub fn cycle_through(issues: &HashMap<u32, Issue>, id: u32, link: Link) -> Option<Vec<u32>> {
+    let mut prev: HashMap<u32, u32> = HashMap::new();
+    let mut queue = VecDeque::from([id]);
+    while let Some(current) = queue.pop_front() {
+        let next_ids = issues
+            .get(&current)
+            .map(|i| link.edges(i))
+            .unwrap_or_default();
+        for next in next_ids {
+            if next == id {
+                let mut path = vec![current];
+                while let Some(&before) = path.last().and_then(|last| prev.get(last)) {
+                    path.push(before);
+                }
+                path.reverse();
+                path.push(id);
+                return Some(path);
+            }
+            if let Entry::Vacant(e) = prev.entry(next) {
+                e.insert(current);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Fails with the cycle path if the dependencies or the parent of `id` close
+/// a cycle. Called before writing an issue whose dependencies or parent
+/// changed, so `ready` never stalls on issues that wait for each other and
+/// epic trees stay trees.
+pub fn ensure_acyclic(issues: &HashMap<u32, Issue>, id: u32) -> Result<()> {
+    for link in Link::ALL {
+        if let Some(cycle) = cycle_through(issues, id, link) {
+            bail!(
+                "This would create a {}: {}",
+                link.cycle_name(),
+                format_cycle(&cycle)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Finds the cycles along links of kind `link`, each as a path that starts
+/// and ends at the same issue. Every cycle found by a depth-first search is
+/// reported once; overlapping cycles may be reported together.
+pub fn cycles(issues: &HashMap<u32, Issue>, link: Link) -> Vec<Vec<u32>> {
+    let mut ids: Vec<u32> = issues.keys().copied().collect();
+    ids.sort_unstable();
+    let mut visited = HashSet::new();
+    let mut found = Vec::new();
+    for id in ids {
+        if !visited.contains(&id) {
+            cycle_dfs(id, issues, link, &mut visited, &mut Vec::new(), &mut found);
+        }
+    }
+    found
+}
+
 fn cycle_dfs(
     id: u32,
     issues: &HashMap<u32, Issue>,
+    link: Link,
     visited: &mut HashSet<u32>,
-    stack: &mut HashSet<u32>,
-) -> bool {
+    path: &mut Vec<u32>,
+    found: &mut Vec<Vec<u32>>,
+) {
     visited.insert(id);
-    stack.insert(id);
-
-    let deps: Vec<u32> = issues
-        .get(&id)
-        .map(|i| {
-            let mut d = i.dependencies.clone();
-            if let Some(p) = i.parent {
-                d.push(p);
-            }
-            d
+    path.push(id);
+    for next in issues.get(&id).map(|i| link.edges(i)).unwrap_or_default() {
+        if let Some(pos) = path.iter().position(|&p| p == next) {
+            let mut cycle = path[pos..].to_vec();
+            cycle.push(next);
+            found.push(cycle);
+        } else if !visited.contains(&next) {
+            cycle_dfs(next, issues, link, visited, path, found);
+        }
+    }
+    path.pop();
+}
+
+/// A consistency problem reported by `llmd issue doctor`.
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    /// `cycle`, `not-in-workflow`, `dangling`, `parent-not-epic`,
+    /// `epic-mismatch`, `duplicate-alias`, or (from `llmd issue doctor`
+    /// itself) `duplicate-id`.
+    pub kind: &'static str,
+    /// The issues involved, starting with the one to fix.
+    pub issues: Vec<u32>,
+    pub message: String,
+}
+
+/// Checks the tracker for dependency and parent cycles, references to missing issues,
+/// parents that are not epics, `parent`/`epic_children` that disagree,
+/// statuses, types or priorities the workflow does not allow, and aliases
+/// shared by several issues.
+pub fn check(issues: &HashMap<u32, Issue>, workflow: &Workflow) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = Link::ALL
+        .into_iter()
+        .flat_map(|link| {
+            cycles(issues, link).into_iter().map(move |cycle| Problem {
+                kind: "cycle",
+                message: format!("{}: {}", link.cycle_name(), format_cycle(&cycle)),
+                issues: cycle,
+            })
         })
-        .unwrap_or_default();
+        .collect();
 
-    for dep in deps {
-        if !visited.contains(&dep) {
-            if cycle_dfs(dep, issues, visited, stack) {
-                return true;
-            }
-        } else if stack.contains(&dep) {
-            return true;
+    let mut sorted: Vec<&Issue> = issues.values().collect();
+    sorted.sort_by_key(|i| i.id);
+    let mut by_alias: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for issue in &sorted {
+        if let Some(alias) = issue.alias {
+            by_alias.entry(alias).or_default().push(issue.id);
         }
     }
+    for issue in sorted {
+        let id = issue.id;
+        for (what, value, allowed) in [
+            ("status", &issue.status, &workflow.statuses),
+            ("type", &issue.issue_type, &workflow.types),
+            ("priority", &issue.priority, &workflow.priorities),
+        ] {
+            if !allowed.contains(value) {
+                problems.push(Problem {
+                    kind: "not-in-workflow",
+                    issues: vec![id],
+                    message: format!(
+                        "#{id} has {what} \"{value}\", which is not in the workflow ({})",
+                        allowed.join(", ")
+                    ),
+                });
+            }
+        }
+        let references = issue
+            .dependencies
+            .iter()
+            .map(|&d| ("dependency", d))
+            .chain(issue.parent.map(|p| ("parent", p)))
+            .chain(issue.epic_children.iter().map(|&c| ("epic child", c)))
+            .chain(match issue.resolution() {
+                Some(Resolution::Duplicate(original)) => Some(("duplicate target", original)),
+                _ => None,
+            });
+        for (what, other) in references {
+            if !issues.contains_key(&other) {
+                problems.push(Problem {
+                    kind: "dangling",
+                    issues: vec![id, other],
+                    message: format!("#{id} has {what} #{other}, which does not exist"),
+                });
+            }
+        }
 
-    stack.remove(&id);
-    false
+        if let Some(parent) = issue.parent.and_then(|p| issues.get(&p)) {
+            if parent.issue_type != "epic" {
+                problems.push(Problem {
+                    kind: "parent-not-epic",
+                    issues: vec![id, parent.id],
+                    message: format!(
+                        "#{id} has parent #{}, which is a {} rather than an epic",
+                        parent.id, parent.issue_type
+                    ),
+                });
+            }
+            if !parent.epic_children.contains(&id) {
+                problems.push(Problem {
+                    kind: "epic-mismatch",
+                    issues: vec![parent.id, id],
+                    message: format!(
+                        "#{id} has parent #{0}, but #{0} does not list it in epic_children",
+                        parent.id
+                    ),
+                });
+            }
+        }
+        for child in issue.epic_children.iter().filter_map(|c| issues.get(c)) {
+            if child.parent != Some(id) {
+                let actual = child
+                    .parent
+                    .map_or("no parent".to_string(), |p| format!("parent #{p}"));
+                problems.push(Problem {
+                    kind: "epic-mismatch",
+                    issues: vec![id, child.id],
+                    message: format!(
+                        "#{id} lists #{} in epic_children, but #{} has {actual}",
+                        child.id, child.id
+                    ),
+                });
+            }
+        }
+    }
+    for (alias, ids) in by_alias.into_iter().filter(|(_, ids)| ids.len() > 1) {
+        let names: Vec<String> = ids.iter().map(|id| format!("#{id}")).collect();
+        problems.push(Problem {
+            kind: "duplicate-alias",
+            issues: ids,
+            message: format!(
+                "{} share alias {alias}; run `llmd issue renumber`",
+                names.join(", ")
+            ),
+        });
+    }
+    problems
 }
 
 /// Returns the epic tree rooted at the given id (for `llmd issue tree`).
@@ -101,3 +321,98 @@ pub fn epic_tree(issues: &HashMap<u32, Issue>, root_id: u32) -> Vec<(u32, usize)
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issues::frontmatter::parse_issue;
+
+    fn issues(specs: &[(u32, &str, &str)]) -> HashMap<u32, Issue> {
+        specs
+            .iter()
+            .map(|&(id, issue_type, extra)| {
+                let content = format!("---\ntitle: Issue {id}\ntype: {issue_type}\n{extra}---\n");
+                (id, parse_issue(&content, id).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cycle_through() {
+        let map = issues(&[
+            (1, "task", "dependencies: [2]\n"),
+            (2, "task", "dependencies: [3]\n"),
+            (3, "task", "dependencies: [1]\n"),
+            (4, "task", "dependencies: [4]\n"),
+            (5, "task", "dependencies: [1]\n"),
+        ]);
+        assert_eq!(
+            cycle_through(&map, 2, Link::Dependency),
+            Some(vec![2, 3, 1, 2])
+        );
+        assert_eq!(cycle_through(&map, 4, Link::Dependency), Some(vec![4, 4]));
+        assert_eq!(cycle_through(&map, 5, Link::Dependency), None);
+
+        // An epic that depends on its own child is not a cycle.
+        let map = issues(&[
+            (1, "epic", "epic_children: [2]\ndependencies: [2]\n"),
+            (2, "task", "parent: 1\n"),
+            (3, "epic", "parent: 4\n"),
+            (4, "epic", "parent: 3\n"),
+        ]);
+        assert!(ensure_acyclic(&map, 1).is_ok());
+        assert!(ensure_acyclic(&map, 2).is_ok());
+        assert_eq!(cycle_through(&map, 3, Link::Parent), Some(vec![3, 4, 3]));
+        assert!(ensure_acyclic(&map, 3).is_err());
+        assert_eq!(format_cycle(&[4, 4]), "#4 → #4");
+    }
+
+    #[test]
+    fn test_ready_tasks_follows_duplicates() {
+        let map = issues(&[
+            (1, "task", "status: closed\nresolution: duplicate:2\n"),
+            (2, "task", ""),
+            (3, "task", "dependencies: [1]\n"),
+            (4, "task", "status: closed\nresolution: wontfix\n"),
+            (5, "task", "dependencies: [4]\n"),
+        ]);
+        let workflow = Workflow::default();
+        let ready: Vec<u32> = ready_tasks(&map, &workflow, true)
+            .iter()
+            .map(|i| i.id)
+            .collect();
+        assert_eq!(ready, vec![2, 5]);
+        assert!(!is_resolved(&map, &workflow, 1));
+        assert!(is_resolved(&map, &workflow, 4));
+    }
+
+    #[test]
+    fn test_check() {
+        let map = issues(&[
+            (1, "epic", "epic_children: [2, 3]\n"),
+            (2, "task", "parent: 1\ndependencies: [9]\n"),
+            (3, "task", "status: Closed\n"),
+            (4, "task", "parent: 3\n"),
+            (5, "task", "dependencies: [6]\n"),
+            (6, "task", "dependencies: [5]\n"),
+            (7, "task", "alias: 2\n"),
+            (8, "task", "alias: 2\n"),
+        ]);
+        let found: Vec<(&str, Vec<u32>)> = check(&map, &Workflow::default())
+            .into_iter()
+            .map(|p| (p.kind, p.issues))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("cycle", vec![5, 6, 5]),
+                ("epic-mismatch", vec![1, 3]),
+                ("dangling", vec![2, 9]),
+                ("not-in-workflow", vec![3]),
+                ("parent-not-epic", vec![4, 3]),
+                ("epic-mismatch", vec![3, 4]),
+                ("duplicate-alias", vec![7, 8]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Issue activity log: `.llmd/issues/.log.jsonl`.
+//!
+//! Every command that changes an issue appends one JSON line per changed
+//! field, recording the old and new value, who made the change and when.
+//! `llmd issue log` shows the history of one issue and `llmd issue activity`
+//! the recent changes across all of them.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::issues::file_ops::now_iso;
+use crate::issues::models::Issue;
+
+/// Name of the log inside .llmd/issues/.
+pub const FILE_NAME: &str = ".log.jsonl";
+
+/// One line of the log: a change to one field of one issue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// ISO 8601 UTC timestamp.
+    pub ts: String,
+    pub issue: u32,
+    /// Frontmatter key, `created` for a new issue or `comment` for a new comment.
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+    pub author: String,
+}
+
+/// Returns the path to `.log.jsonl` inside the issues dir.
+pub fn path(issues_dir: &Path) -> PathBuf {
+    issues_dir.join(FILE_NAME)
+}
+
+/// Appends an event for each difference between `before` (`None` for a new
+/// issue) and `after`. The log must never break the command that changed the
+/// issue, so failures are reported as warnings.
+pub fn record(issues_dir: &Path, before: Option<&Issue>, after: &Issue, author: &str) {
+    let events: Vec<Event> = changes(before, after)
+        .into_iter()
+        .map(|(field, old, new)| Event {
+            ts: now_iso(),
+            issue: after.id,
+            field: field.to_string(),
+            old,
+            new,
+            author: author.to_string(),
+        })
+        .collect();
+    if events.is_empty() {
+        return;
+    }
+    if let Err(e) = append(issues_dir, &events) {
+        eprintln!("Warning: could not write the issue log: {e:#}");
+    }
+}
+
+fn append(issues_dir: &Path, events: &[Event]) -> Result<()> {
+    let path = path(issues_dir);
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event)?);
+        lines.push('\n');
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Cannot open {}", path.display()))?;
+    file.write_all(lines.as_bytes())
+        .with_context(|| format!("Cannot write {}", path.display()))
+}
+
+/// Reads every event in the log, skipping (and counting) malformed lines.
+pub fn load(issues_dir: &Path) -> Result<(Vec<Event>, usize)> {
+    let path = path(issues_dir);
+    if !path.is_file() {
+        return Ok((Vec::new(), 0));
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(event) => events.push(event),
+            Err(_) => skipped += 1,
+        }
+    }
+    Ok((events, skipped))
+}
+
+/// The fields that differ between `before` and `after`, as `(field, old,
+/// new)`. Timestamps that every write touches (`updated_at`, `closed_at`)
+/// are left out; a new comment is reported as `comment` with its id and
+/// first line.
+pub fn changes(before: Option<&Issue>, after: &Issue) -> Vec<(&'static str, Value, Value)> {
+    let Some(before) = before else {
+        return vec![("created", Value::Null, json!(after.title))];
+    };
+    let labels = |i: &Issue| json!(i.labels.iter().map(|l| &l.name).collect::<Vec<_>>());
+    let fields = |i: &Issue| {
+        [
+            ("id", json!(i.id)),
+            ("alias", json!(i.alias)),
+            ("title", json!(i.title)),
+            ("type", json!(i.issue_type)),
+            ("status", json!(i.status)),
+            ("priority", json!(i.priority)),
+            ("labels", labels(i)),
+            ("assignee", json!(i.assignee)),
+            ("handover_to", json!(i.handover_to)),
+            ("milestone", json!(i.milestone)),
+            ("parent", json!(i.parent)),
+            ("dependencies", json!(i.dependencies)),
+            ("epic_children", json!(i.epic_children)),
+            ("points", json!(i.points)),
+            ("due", json!(i.due)),
+            ("resolution", json!(i.resolution)),
+            ("lease_expires_at", json!(i.lease_expires_at)),
+        ]
+    };
+    let mut out: Vec<_> = fields(before)
+        .into_iter()
+        .zip(fields(after))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| (field, old, new))
+        .collect();
+    for comment in &after.comments {
+        if !before.comments.iter().any(|c| c.id == comment.id) {
+            let first_line = comment.body.lines().next().unwrap_or_default();
+            out.push((
+                "comment",
+                Value::Null,
+                json!(format!("[{}] {first_line}", comment.id)),
+            ));
+        }
+    }
+    out
+}
+
+/// A value as shown in `llmd issue log`: strings unquoted, lists joined,
+/// `—` for null and empty lists.
+pub fn display(value: &Value) -> String {
+    match value {
+        Value::Null => "—".to_string(),
+        Value::Array(items) if items.is_empty() => "—".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes() {
+        let before = Issue {
+            id: 3,
+            title: "Fix login".to_string(),
+            status: "open".to_string(),
+            ..Default::default()
+        };
+        let mut after = before.clone();
+        after.status = "in_progress".to_string();
+        after.dependencies = vec![1, 2];
+        after.updated_at = "later".to_string();
+        after.add_comment("ana", "now", "On it\nsecond line", None);
+
+        assert_eq!(
+            changes(Some(&before), &after),
+            vec![
+                ("status", json!("open"), json!("in_progress")),
+                ("dependencies", json!([]), json!([1, 2])),
+                ("comment", Value::Null, json!("[1] On it")),
+            ]
+        );
+        assert_eq!(
+            changes(None, &before),
+            vec![("created", Value::Null, json!("Fix login"))]
+        );
+        assert!(changes(Some(&before), &before).is_empty());
+        assert_eq!(display(&json!([1, 2])), "1, 2");
+        assert_eq!(display(&json!([])), "—");
+    }
+}
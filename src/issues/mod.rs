@@ -1,9 +1,13 @@
 //! Issue tracker: parse, load, and query issues from .llmd/issues/.
 
+pub mod comments;
 pub mod file_ops;
 pub mod frontmatter;
 pub mod graph;
+pub mod log;
 pub mod models;
+pub mod renumber;
+pub mod workflow;
 
 pub use file_ops::{
     load_all_issues, load_config, now_iso, resolve_issue_path, save_config, write_issue,
@@ -1,19 +1,96 @@
 //! Data structures for the issue tracker.
 
 use serde::{Deserialize, Serialize};
+use serde_yaml_ng::Mapping;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::issues::workflow::Workflow;
 
 /// Issue tracker config stored in .llmd/issues/config.json
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub next_id: u32,
+    #[serde(default)]
+    pub id_scheme: IdScheme,
+    #[serde(default)]
+    pub workflow: Workflow,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self { next_id: 1 }
+        Self {
+            next_id: 1,
+            id_scheme: IdScheme::default(),
+            workflow: Workflow::default(),
+        }
     }
 }
 
+/// How `llmd issue new` picks ids.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum IdScheme {
+    /// 1, 2, 3, … from `next_id`. Two branches that both create issues
+    /// allocate the same ids.
+    #[default]
+    Sequential,
+    /// Seven-digit ids derived from a hash of the title, time and process,
+    /// plus a sequential `alias` for display. `next_id` is not used, so
+    /// config.json does not change when issues are created.
+    Hash,
+}
+
+/// Smallest hash id; sequential ids and aliases stay below it in practice.
+const HASH_ID_MIN: u32 = 1_000_000;
+const HASH_ID_SPAN: u64 = 9_000_000;
+
+impl Config {
+    /// Picks an id for a new issue that no issue in `issues` uses. `seed`
+    /// should differ between issues created at the same moment.
+    pub fn allocate_id(&mut self, issues: &HashMap<u32, Issue>, seed: &str) -> u32 {
+        match self.id_scheme {
+            IdScheme::Sequential => {
+                // After a merge, next_id may lag behind ids from the other branch.
+                let after_max = issues.keys().max().map_or(1, |max| max + 1);
+                let id = self.next_id.max(after_max);
+                self.next_id = id + 1;
+                id
+            }
+            IdScheme::Hash => (0u32..)
+                .map(|n| hash_id(&format!("{seed}\0{n}")))
+                .find(|id| !issues.contains_key(id))
+                .expect("the id space is not exhausted"),
+        }
+    }
+}
+
+fn hash_id(seed: &str) -> u32 {
+    let digest = Sha256::digest(seed.as_bytes());
+    let n = u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"));
+    HASH_ID_MIN + (n % HASH_ID_SPAN) as u32
+}
+
+/// The alias after the highest one in use.
+pub fn next_alias(issues: &HashMap<u32, Issue>) -> u32 {
+    issues.values().filter_map(|i| i.alias).max().unwrap_or(0) + 1
+}
+
+/// The id of the issue a user-supplied number refers to: an id if one
+/// matches, else an alias (the same precedence as `resolve_issue_path`).
+/// Fails if no issue has it, so writes never store a dangling reference.
+pub fn resolve_id(issues: &HashMap<u32, Issue>, id_or_alias: u32) -> anyhow::Result<u32> {
+    if issues.contains_key(&id_or_alias) {
+        return Ok(id_or_alias);
+    }
+    issues
+        .values()
+        .find(|i| i.alias == Some(id_or_alias))
+        .map(|i| i.id)
+        .ok_or_else(|| anyhow::anyhow!("Issue #{id_or_alias} not found"))
+}
+
 /// A label on an issue.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Label {
@@ -22,19 +99,32 @@ pub struct Label {
     pub color: Option<String>,
 }
 
-/// A comment on an issue.
-#[allow(dead_code)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A comment on an issue, kept in the `## Comments` section of its file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Comment {
+    /// Number of the comment within its issue, starting at 1. Comments
+    /// written before ids existed get the number of their position.
+    #[serde(default)]
+    pub id: u32,
     pub author: String,
     pub date: String,
+    /// The comment this one answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<u32>,
+    /// Set on the comment `llmd issue handoff` writes: the agent the issue
+    /// was handed to. The body is the outgoing agent's summary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handoff_to: Option<String>,
     pub body: String,
 }
 
 /// Parsed issue from frontmatter + body.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Issue {
     pub id: u32,
+    /// Short sequential number shown next to a hash id. Commands that take
+    /// an issue also accept its alias.
+    pub alias: Option<u32>,
     pub title: String,
     pub slug: String,
     pub issue_type: String,
@@ -42,6 +132,9 @@ pub struct Issue {
     pub priority: String,
     pub labels: Vec<Label>,
     pub assignee: Option<String>,
+    /// Agent an issue was handed to with `llmd issue handoff`, until it
+    /// claims the issue.
+    pub handover_to: Option<String>,
     pub milestone: Option<String>,
     pub parent: Option<u32>,
     pub dependencies: Vec<u32>,
@@ -50,5 +143,150 @@ pub struct Issue {
     pub due: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// How the issue was closed: `done`, `wontfix` or `duplicate:<id>`.
+    pub resolution: Option<String>,
+    pub closed_at: Option<String>,
+    /// When the current claim (`llmd issue claim`) runs out.
+    pub lease_expires_at: Option<String>,
+    /// The body without the `## Comments` section, which is parsed into
+    /// `comments`.
     pub body: String,
+    pub comments: Vec<Comment>,
+    /// The frontmatter as read from disk, including keys llmd does not know.
+    /// Empty for new issues. Known keys are taken from the fields above when
+    /// the issue is written.
+    pub frontmatter: Mapping,
+}
+
+impl Issue {
+    /// `#id`, followed by the alias if the issue has one: `#4821937 (12)`.
+    pub fn display_id(&self) -> String {
+        match self.alias {
+            Some(alias) => format!("#{} ({alias})", self.id),
+            None => format!("#{}", self.id),
+        }
+    }
+
+    /// True while a claim on the issue has not expired.
+    pub fn has_live_lease(&self) -> bool {
+        self.lease_expires_at
+            .as_deref()
+            .is_some_and(crate::issues::file_ops::is_future)
+    }
+
+    /// Appends a comment and returns its id.
+    pub fn add_comment(
+        &mut self,
+        author: &str,
+        date: &str,
+        body: &str,
+        reply_to: Option<u32>,
+    ) -> u32 {
+        let id = self.comments.iter().map(|c| c.id).max().unwrap_or(0) + 1;
+        self.comments.push(Comment {
+            id,
+            author: author.to_string(),
+            date: date.to_string(),
+            reply_to,
+            handoff_to: None,
+            body: body.to_string(),
+        });
+        id
+    }
+
+    /// The parsed `resolution`, if it is set and valid.
+    pub fn resolution(&self) -> Option<Resolution> {
+        self.resolution.as_deref().and_then(Resolution::parse)
+    }
+}
+
+/// How a closed issue was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Done,
+    WontFix,
+    /// Closed in favour of the given issue, which carries on the work.
+    Duplicate(u32),
+}
+
+impl Resolution {
+    /// Parses `done`, `wontfix` or `duplicate:<id>`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "done" => Some(Self::Done),
+            "wontfix" => Some(Self::WontFix),
+            other => other
+                .strip_prefix("duplicate:")?
+                .trim()
+                .trim_start_matches('#')
+                .parse()
+                .ok()
+                .map(Self::Duplicate),
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Done => write!(f, "done"),
+            Self::WontFix => write!(f, "wontfix"),
+            Self::Duplicate(id) => write!(f, "duplicate:{id}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_id() {
+        let mut issues = HashMap::new();
+        issues.insert(7, Issue::default());
+        let mut config = Config::default();
+        assert_eq!(config.allocate_id(&issues, "a"), 8);
+        assert_eq!(config.next_id, 9);
+
+        config.id_scheme = IdScheme::Hash;
+        let id = config.allocate_id(&issues, "a");
+        assert!((HASH_ID_MIN..10_000_000).contains(&id));
+        assert_eq!(config.allocate_id(&issues, "a"), id);
+        assert_ne!(config.allocate_id(&issues, "b"), id);
+        issues.insert(id, Issue::default());
+        assert_ne!(config.allocate_id(&issues, "a"), id);
+        assert_eq!(config.next_id, 9);
+    }
+
+    #[test]
+    fn test_resolve_id() {
+        let mut issues = HashMap::new();
+        for (id, alias) in [(1, None), (1_234_567, Some(1)), (2_345_678, Some(2))] {
+            issues.insert(
+                id,
+                Issue {
+                    id,
+                    alias,
+                    ..Default::default()
+                },
+            );
+        }
+        // An id wins over an alias with the same number.
+        assert_eq!(resolve_id(&issues, 1).unwrap(), 1);
+        assert_eq!(resolve_id(&issues, 2).unwrap(), 2_345_678);
+        assert_eq!(resolve_id(&issues, 1_234_567).unwrap(), 1_234_567);
+        assert!(resolve_id(&issues, 3).is_err());
+    }
+
+    #[test]
+    fn test_resolution_parse() {
+        assert_eq!(Resolution::parse("done"), Some(Resolution::Done));
+        assert_eq!(
+            Resolution::parse("duplicate:#12"),
+            Some(Resolution::Duplicate(12))
+        );
+        assert_eq!(Resolution::parse("duplicate:x"), None);
+        assert_eq!(Resolution::parse("fixed"), None);
+        assert_eq!(Resolution::Duplicate(12).to_string(), "duplicate:12");
+    }
 }
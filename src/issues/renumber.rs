@@ -0,0 +1,214 @@
+//! Repairs duplicate ids and aliases, e.g. after merging two branches that
+//! both created issues. `llmd issue renumber` prints and writes the plan
+//! [`plan`] returns.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use crate::issues::models::{self, Config, IdScheme, Issue};
+
+/// An issue file that moves to a new id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub path: PathBuf,
+    pub old: u32,
+    pub new: u32,
+}
+
+/// The outcome of [`plan`]; nothing has been written yet.
+#[derive(Debug, Default)]
+pub struct Renumbering {
+    /// Every issue, by the id it ends up with.
+    pub issues: HashMap<u32, Issue>,
+    /// The issues as read, by the id they end up with, for the activity log.
+    pub before: HashMap<u32, Issue>,
+    pub moves: Vec<Move>,
+    /// `(id, old alias, new alias)` for each issue whose alias changed.
+    pub aliases: Vec<(u32, u32, u32)>,
+    /// Issues that have to be rewritten.
+    pub dirty: BTreeSet<u32>,
+}
+
+impl Renumbering {
+    /// The issues whose dependencies or parent name `old`. They are left
+    /// alone: the reference may have meant either issue that had the id.
+    pub fn referrers(&self, old: u32) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .issues
+            .values()
+            .filter(|i| i.dependencies.contains(&old) || i.parent == Some(old))
+            .map(|i| i.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+}
+
+/// Plans the repair of `files` (each issue with the path it was read from).
+///
+/// Of the issues sharing an id, the oldest keeps it and the others get new
+/// ids from `config`; a moved issue is added to its epic's `epic_children`.
+/// Of the issues sharing an alias, the oldest keeps it and the others get new
+/// ones. With sequential ids, `config.next_id` is moved past the highest id.
+pub fn plan(config: &mut Config, files: Vec<(PathBuf, Issue)>) -> Renumbering {
+    let mut by_id: BTreeMap<u32, Vec<(PathBuf, Issue)>> = BTreeMap::new();
+    for (path, issue) in files {
+        by_id.entry(issue.id).or_default().push((path, issue));
+    }
+
+    let mut out = Renumbering::default();
+    let mut to_move = Vec::new();
+    for (id, mut files) in by_id {
+        files.sort_by(|a, b| (&a.1.created_at, &a.0).cmp(&(&b.1.created_at, &b.0)));
+        let mut files = files.into_iter();
+        if let Some((_, kept)) = files.next() {
+            out.issues.insert(id, kept);
+        }
+        to_move.extend(files);
+    }
+
+    out.before = out.issues.clone();
+    for (path, mut issue) in to_move {
+        let old = issue.id;
+        let seed = format!("{}\0{}", path.display(), issue.created_at);
+        let id = config.allocate_id(&out.issues, &seed);
+
+        // The moved issue's own parent is unambiguous; references to the old
+        // id from other issues are not (see `Renumbering::referrers`).
+        if let Some(parent) = issue.parent.and_then(|p| out.issues.get_mut(&p))
+            && parent.issue_type == "epic"
+            && !parent.epic_children.contains(&id)
+        {
+            parent.epic_children.push(id);
+            parent.epic_children.sort();
+            out.dirty.insert(parent.id);
+        }
+        out.before.insert(id, issue.clone());
+        issue.id = id;
+        out.issues.insert(id, issue);
+        out.dirty.insert(id);
+        out.moves.push(Move { path, old, new: id });
+    }
+
+    let mut by_alias: BTreeMap<u32, Vec<(String, u32)>> = BTreeMap::new();
+    for issue in out.issues.values() {
+        if let Some(alias) = issue.alias {
+            by_alias
+                .entry(alias)
+                .or_default()
+                .push((issue.created_at.clone(), issue.id));
+        }
+    }
+    for (alias, mut holders) in by_alias {
+        holders.sort();
+        for (_, id) in holders.into_iter().skip(1) {
+            let new_alias = models::next_alias(&out.issues);
+            if let Some(issue) = out.issues.get_mut(&id) {
+                issue.alias = Some(new_alias);
+            }
+            out.aliases.push((id, alias, new_alias));
+            out.dirty.insert(id);
+        }
+    }
+
+    if config.id_scheme == IdScheme::Sequential
+        && let Some(&max) = out.issues.keys().max()
+        && config.next_id <= max
+    {
+        config.next_id = max + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(id: u32, created_at: &str) -> Issue {
+        Issue {
+            id,
+            title: format!("created {created_at}"),
+            issue_type: "task".to_string(),
+            created_at: created_at.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_sequential() {
+        let epic = Issue {
+            issue_type: "epic".to_string(),
+            epic_children: vec![2],
+            ..issue(1, "2026-01-01")
+        };
+        let ours = Issue {
+            parent: Some(1),
+            ..issue(2, "2026-01-02")
+        };
+        // The same id from the other branch, created later.
+        let theirs = Issue {
+            parent: Some(1),
+            ..issue(2, "2026-01-03")
+        };
+        let blocked = Issue {
+            dependencies: vec![2],
+            ..issue(3, "2026-01-04")
+        };
+        let mut config = Config {
+            next_id: 3,
+            ..Default::default()
+        };
+        let plan = plan(
+            &mut config,
+            vec![
+                ("001-epic.md".into(), epic),
+                ("002-theirs.md".into(), theirs),
+                ("002-ours.md".into(), ours),
+                ("003-blocked.md".into(), blocked),
+            ],
+        );
+
+        assert_eq!(
+            plan.moves,
+            vec![Move {
+                path: "002-theirs.md".into(),
+                old: 2,
+                new: 4
+            }]
+        );
+        assert_eq!(plan.issues[&2].created_at, "2026-01-02");
+        assert_eq!(plan.issues[&4].created_at, "2026-01-03");
+        assert_eq!(plan.issues[&1].epic_children, vec![2, 4]);
+        assert_eq!(plan.before[&4].id, 2);
+        assert_eq!(plan.dirty, BTreeSet::from([1, 4]));
+        assert_eq!(plan.referrers(2), vec![3]);
+        assert_eq!(config.next_id, 5);
+    }
+
+    #[test]
+    fn test_plan_aliases() {
+        let mut config = Config {
+            id_scheme: IdScheme::Hash,
+            ..Default::default()
+        };
+        let files = [
+            (1_000_001, Some(1), "2026-01-02"),
+            (2_000_002, Some(1), "2026-01-01"),
+            (3_000_003, Some(2), "2026-01-03"),
+        ]
+        .map(|(id, alias, created_at)| {
+            let issue = Issue {
+                alias,
+                ..issue(id, created_at)
+            };
+            (PathBuf::from(format!("{id}.md")), issue)
+        });
+        let plan = plan(&mut config, files.to_vec());
+
+        assert!(plan.moves.is_empty());
+        assert_eq!(plan.aliases, vec![(1_000_001, 1, 3)]);
+        assert_eq!(plan.issues[&2_000_002].alias, Some(1));
+        assert_eq!(plan.dirty, BTreeSet::from([1_000_001]));
+        assert_eq!(config.next_id, 1);
+    }
+}
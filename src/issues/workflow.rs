@@ -0,0 +1,204 @@
+//! Issue workflow: allowed statuses, types and priorities, status transitions
+//! and terminal statuses, configured in `.llmd/issues/config.json`.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The `workflow` block of config.json. Missing keys take their defaults, so
+/// trackers created before the block existed keep working.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workflow {
+    /// Allowed statuses. New issues get the first one.
+    pub statuses: Vec<String>,
+    /// Statuses that end an issue: it no longer shows up as ready or open, and
+    /// issues depending on it are unblocked. `llmd issue close` uses `closed`
+    /// if it is terminal, otherwise the first terminal status.
+    pub terminal: Vec<String>,
+    /// Status → statuses it may change to. A status without an entry may
+    /// change to any status.
+    pub transitions: BTreeMap<String, Vec<String>>,
+    pub types: Vec<String>,
+    /// Allowed priorities, most urgent first; `ready` sorts by this order.
+    pub priorities: Vec<String>,
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        Self {
+            statuses: strings(&["open", "in_progress", "closed"]),
+            terminal: strings(&["closed"]),
+            transitions: BTreeMap::new(),
+            types: strings(&["task", "bug", "feature", "chore", "spike", "epic"]),
+            priorities: strings(&["high", "medium", "low"]),
+        }
+    }
+}
+
+impl Workflow {
+    /// Checks that the lists are non-empty and that terminal statuses and
+    /// transitions only name allowed statuses.
+    pub fn validate(&self) -> Result<()> {
+        for (name, list) in [
+            ("statuses", &self.statuses),
+            ("terminal", &self.terminal),
+            ("types", &self.types),
+            ("priorities", &self.priorities),
+        ] {
+            if list.is_empty() {
+                bail!("workflow.{name} must not be empty");
+            }
+        }
+        let transition_statuses = self
+            .transitions
+            .iter()
+            .flat_map(|(from, to)| std::iter::once(from).chain(to));
+        for status in self.terminal.iter().chain(transition_statuses) {
+            if !self.statuses.contains(status) {
+                bail!("workflow refers to status \"{status}\", which is not in workflow.statuses");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_terminal(&self, status: &str) -> bool {
+        self.terminal.iter().any(|s| s == status)
+    }
+
+    /// The status given to new issues.
+    pub fn initial_status(&self) -> &str {
+        &self.statuses[0]
+    }
+
+    /// The status `llmd issue claim` sets: `in_progress`, if the workflow
+    /// has it.
+    pub fn active_status(&self) -> Option<&str> {
+        self.statuses
+            .iter()
+            .find(|s| *s == "in_progress")
+            .map(String::as_str)
+    }
+
+    /// The status `llmd issue close` sets.
+    pub fn closed_status(&self) -> &str {
+        self.terminal
+            .iter()
+            .find(|s| *s == "closed")
+            .unwrap_or(&self.terminal[0])
+    }
+
+    /// `task` if allowed, otherwise the first type.
+    pub fn default_type(&self) -> &str {
+        self.types
+            .iter()
+            .find(|s| *s == "task")
+            .unwrap_or(&self.types[0])
+    }
+
+    /// `medium` if allowed, otherwise the middle priority.
+    pub fn default_priority(&self) -> &str {
+        self.priorities
+            .iter()
+            .find(|s| *s == "medium")
+            .unwrap_or(&self.priorities[self.priorities.len() / 2])
+    }
+
+    /// Sort key for a priority: its position in the list, unknown ones last.
+    pub fn priority_rank(&self, priority: &str) -> usize {
+        self.priorities
+            .iter()
+            .position(|p| p == priority)
+            .unwrap_or(self.priorities.len())
+    }
+
+    pub fn check_status(&self, status: &str) -> Result<()> {
+        check(&self.statuses, "status", status)
+    }
+
+    pub fn check_type(&self, issue_type: &str) -> Result<()> {
+        check(&self.types, "type", issue_type)
+    }
+
+    pub fn check_priority(&self, priority: &str) -> Result<()> {
+        check(&self.priorities, "priority", priority)
+    }
+
+    /// Checks that `to` is an allowed status and that moving from `from` is
+    /// allowed. Staying put is always allowed, as is leaving a status the
+    /// workflow does not know.
+    pub fn check_transition(&self, from: &str, to: &str) -> Result<()> {
+        self.check_status(to)?;
+        if from == to {
+            return Ok(());
+        }
+        if let Some(allowed) = self.transitions.get(from)
+            && !allowed.iter().any(|s| s == to)
+        {
+            bail!(
+                "Cannot move an issue from \"{from}\" to \"{to}\". Allowed from \"{from}\": {} (see workflow.transitions in issues/config.json)",
+                if allowed.is_empty() {
+                    "nothing".to_string()
+                } else {
+                    allowed.join(", ")
+                }
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Fails unless `value` is one of `allowed`, suggesting a case-insensitive match.
+fn check(allowed: &[String], what: &str, value: &str) -> Result<()> {
+    if allowed.iter().any(|a| a == value) {
+        return Ok(());
+    }
+    let hint = allowed
+        .iter()
+        .find(|a| a.eq_ignore_ascii_case(value))
+        .map(|a| format!(" Did you mean \"{a}\"?"))
+        .unwrap_or_default();
+    bail!(
+        "Unknown {what} \"{value}\".{hint} Allowed: {} (see issues/config.json)",
+        allowed.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_transition() {
+        let mut workflow = Workflow::default();
+        workflow.statuses.push("review".to_string());
+        workflow
+            .transitions
+            .insert("open".to_string(), strings(&["in_progress"]));
+        assert!(workflow.validate().is_ok());
+        assert!(workflow.check_transition("open", "in_progress").is_ok());
+        assert!(workflow.check_transition("open", "open").is_ok());
+        assert!(workflow.check_transition("open", "closed").is_err());
+        assert!(workflow.check_transition("review", "closed").is_ok());
+        let err = workflow.check_transition("review", "Closed").unwrap_err();
+        assert!(err.to_string().contains("Did you mean \"closed\"?"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let workflow: Workflow =
+            serde_json::from_str(r#"{"statuses": ["todo", "done"], "terminal": ["done"]}"#)
+                .unwrap();
+        assert!(workflow.validate().is_ok());
+        assert_eq!(workflow.closed_status(), "done");
+        assert_eq!(workflow.default_priority(), "medium");
+
+        let mut bad = workflow.clone();
+        bad.terminal = strings(&["closed"]);
+        assert!(bad.validate().is_err());
+    }
+}
@@ -2,15 +2,140 @@
 //!
 //! Searches upward from the current working directory to find the project root
 //! (identified by the presence of .llmd/, Cargo.toml, package.json, .git, etc.).
+//! The global `--llmd-dir` flag or `LLMD_DIR` variable names the directory
+//! explicitly, and `--root` moves the start of the search (see [`locate`]).
+//! Also owns the project config (`.llmd/llmd.json`), which is loaded and
+//! validated once, when the directory is found.
+//!
+//! In a monorepo, `.llmd/` directories in enclosing directories form further
+//! layers (see [`layers`]): read, index, search and compose see all of them,
+//! with files in nearer layers shadowing files at the same path further up.
+//! Knowledge packs declared in the config are appended as read-only layers
+//! under a `pack:<name>` prefix and never shadow or get shadowed.
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::{self, Config};
+
+/// Configs already loaded in this process, keyed by `.llmd/` path.
+static CONFIGS: Mutex<BTreeMap<PathBuf, Config>> = Mutex::new(BTreeMap::new());
+
+/// Layer stacks already resolved in this process, keyed by nearest `.llmd/` path.
+static LAYERS: Mutex<BTreeMap<PathBuf, Vec<Layer>>> = Mutex::new(BTreeMap::new());
+
+/// One `.llmd/` directory in a layered workspace.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    /// The `.llmd/` directory itself.
+    pub dir: PathBuf,
+    /// Label prefix for entries from this layer: `None` for the nearest layer,
+    /// otherwise the name of the directory that holds the `.llmd/`, or the
+    /// pack name.
+    pub name: Option<String>,
+    /// True for knowledge packs (see `llmd pack`).
+    pub pack: bool,
+}
+
+impl Layer {
+    /// Qualifies a path inside this layer for display: `rel` in the nearest
+    /// layer, `<name>:rel` in enclosing layers and `pack:<name> > rel` in packs.
+    pub fn qualify(&self, rel: &str) -> String {
+        match (&self.name, self.pack) {
+            (None, _) => rel.to_string(),
+            (Some(name), false) => format!("{name}:{rel}"),
+            (Some(name), true) => format!("pack:{name} > {rel}"),
+        }
+    }
+}
+
+/// The global `--llmd-dir` and `--root` options, set once by `main`.
+static SELECTION: OnceLock<Selection> = OnceLock::new();
+
+/// Workspace selection from the global command-line options.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// `--llmd-dir`: the `.llmd/` directory to use (or a directory containing one).
+    pub llmd_dir: Option<PathBuf>,
+    /// `--root`: where to start searching upward for `.llmd/`.
+    pub root: Option<PathBuf>,
+}
+
+/// Records the global options for [`locate`] and [`start_dir`]. Called once from `main`.
+pub fn select(selection: Selection) {
+    let _ = SELECTION.set(selection);
+}
+
+/// Returns the directory commands treat as the project root: `--root` if
+/// given, otherwise the current directory.
+pub fn start_dir() -> Result<PathBuf> {
+    match SELECTION.get().and_then(|s| s.root.clone()) {
+        Some(root) => Ok(root),
+        None => std::env::current_dir().context("Cannot read the current directory"),
+    }
+}
+
+/// Resolves the `.llmd/` directory for this invocation, in order of precedence:
+///
+/// 1. `--llmd-dir <DIR>`
+/// 2. the `LLMD_DIR` environment variable
+/// 3. searching upward from `--root <DIR>`
+/// 4. searching upward from the current directory
+///
+/// Errors name the method that was used, so a stale `LLMD_DIR` is easy to spot.
+/// Also fails if the directory's `llmd.json` is invalid.
+pub fn locate() -> Result<PathBuf> {
+    let llmd = locate_unvalidated()?;
+    config(&llmd)?;
+    Ok(llmd)
+}
+
+/// Like [`locate`], but does not load `llmd.json`, so `llmd config` can show
+/// and repair an invalid one.
+pub fn locate_unvalidated() -> Result<PathBuf> {
+    let selection = SELECTION.get();
+    if let Some(dir) = selection.and_then(|s| s.llmd_dir.as_deref()) {
+        return explicit(dir, "--llmd-dir");
+    }
+    if let Some(dir) = std::env::var_os("LLMD_DIR").filter(|v| !v.is_empty()) {
+        return explicit(Path::new(&dir), "the LLMD_DIR environment variable");
+    }
+    match selection.and_then(|s| s.root.as_deref()) {
+        Some(root) => find(root, "--root"),
+        None => find(&start_dir()?, "the current directory"),
+    }
+}
+
+/// Uses a directory named explicitly: the `.llmd/` inside it if there is one,
+/// otherwise the directory itself.
+fn explicit(dir: &Path, method: &str) -> Result<PathBuf> {
+    let llmd = if dir.join(".llmd").is_dir() {
+        dir.join(".llmd")
+    } else if dir.is_dir() {
+        dir.to_path_buf()
+    } else {
+        bail!(
+            "{} is not a directory (set by {method}). \
+             Point it at an existing .llmd/ directory.",
+            dir.display()
+        );
+    };
+    llmd.canonicalize()
+        .with_context(|| format!("Cannot resolve {} (set by {method})", llmd.display()))
+}
 
 /// Resolves the path to the `.llmd/` directory, searching upward from `start`.
 ///
-/// Returns an error if no `.llmd/` directory is found. Use `llmd init` to create one.
-pub fn find(start: &Path) -> Result<PathBuf> {
-    let mut current = start.to_path_buf();
+/// Returns an error if no `.llmd/` directory is found (use `llmd init` to
+/// create one). `method` describes where `start` came from, for the error
+/// message.
+fn find(start: &Path, method: &str) -> Result<PathBuf> {
+    let start = start
+        .canonicalize()
+        .with_context(|| format!("Cannot resolve {} (set by {method})", start.display()))?;
+    let mut current = start.clone();
     loop {
         let candidate = current.join(".llmd");
         if candidate.is_dir() {
@@ -19,20 +144,310 @@ pub fn find(start: &Path) -> Result<PathBuf> {
         match current.parent() {
             Some(parent) => current = parent.to_path_buf(),
             None => bail!(
-                "No .llmd/ directory found. Run `llmd init` in your project root to create one."
+                "No .llmd/ directory found in {} or any parent directory (searched from {method}). \
+                 Run `llmd init` in your project root to create one, or pass --llmd-dir.",
+                start.display()
             ),
         }
     }
 }
 
+/// Returns the project config for the given `.llmd/` directory, loading and
+/// validating `llmd.json` on first use.
+pub fn config(llmd: &Path) -> Result<Config> {
+    let mut cache = CONFIGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(config) = cache.get(llmd) {
+        return Ok(config.clone());
+    }
+    let config = config::load(llmd)?;
+    cache.insert(llmd.to_path_buf(), config.clone());
+    Ok(config)
+}
+
+/// Reads `llmd.json` again, for long-running commands such as `llmd watch`.
+/// On success the cached config and layer stacks are replaced; on error the
+/// cache is left as it was.
+pub fn reload_config(llmd: &Path) -> Result<Config> {
+    let config = config::load(llmd)?;
+    CONFIGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(llmd.to_path_buf(), config.clone());
+    LAYERS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    Ok(config)
+}
+
+/// Returns the config if it loads, or the defaults otherwise. `locate()` has
+/// already reported any error, so path helpers can stay infallible.
+fn config_or_default(llmd: &Path) -> Config {
+    config(llmd).unwrap_or_default()
+}
+
+/// Returns the layers visible from `llmd`, nearest first: `llmd` itself, then
+/// each `.llmd/` found walking upward from its project directory.
+///
+/// The walk stops at a layer whose project directory is a git repository root
+/// (contains `.git`) or whose config sets `workspace.inherit` to `false`.
+/// The packs declared in the nearest layer's config follow, in name order; a
+/// pack whose directory is missing (say, a git pack not vendored yet) is
+/// skipped with a warning so it cannot break every other command.
+pub fn layers(llmd: &Path) -> Result<Vec<Layer>> {
+    if let Some(layers) = LAYERS.lock().unwrap_or_else(|e| e.into_inner()).get(llmd) {
+        return Ok(layers.clone());
+    }
+
+    let mut layers = vec![Layer {
+        dir: llmd.to_path_buf(),
+        name: None,
+        pack: false,
+    }];
+    let mut dir = llmd.to_path_buf();
+    loop {
+        let project = dir.parent().unwrap_or(&dir);
+        if project.join(".git").exists() || !config(&dir)?.workspace.inherit {
+            break;
+        }
+        let Some(next) = project
+            .ancestors()
+            .skip(1)
+            .map(|a| a.join(".llmd"))
+            .find(|c| c.is_dir())
+        else {
+            break;
+        };
+        let owner = next.parent().unwrap_or(&next);
+        let name = owner
+            .file_name()
+            .map_or_else(|| "root".to_string(), |n| n.to_string_lossy().into_owned());
+        layers.push(Layer {
+            dir: next.clone(),
+            name: Some(name),
+            pack: false,
+        });
+        dir = next;
+    }
+    for name in config(llmd)?.packs.keys() {
+        match pack_dir(llmd, name) {
+            Ok(dir) => layers.push(Layer {
+                dir,
+                name: Some(name.clone()),
+                pack: true,
+            }),
+            Err(e) => eprintln!("Warning: skipping pack \"{name}\": {e:#}"),
+        }
+    }
+
+    LAYERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(llmd.to_path_buf(), layers.clone());
+    Ok(layers)
+}
+
+/// Returns the directory holding the files of pack `name`: the vendored copy
+/// in `.llmd/packs/<name>/` for git packs, or the configured path (or the
+/// `.llmd/` inside it) for local packs.
+pub fn pack_dir(llmd: &Path, name: &str) -> Result<PathBuf> {
+    let config = config(llmd)?;
+    let pack = config.packs.get(name).with_context(|| {
+        format!(
+            "No pack named \"{name}\" in {}",
+            config::path(llmd).display()
+        )
+    })?;
+    let dir = match &pack.path {
+        Some(path) => {
+            let project = llmd.parent().unwrap_or(llmd);
+            let dir = project.join(path);
+            if dir.join(".llmd").is_dir() {
+                dir.join(".llmd")
+            } else {
+                dir
+            }
+        }
+        None => packs_path(llmd).join(name),
+    };
+    if !dir.is_dir() {
+        bail!(
+            "Pack \"{name}\" not found at {}. {}",
+            dir.display(),
+            if pack.git.is_some() {
+                format!("Run `llmd pack update {name}` to vendor it.")
+            } else {
+                "Check its path in llmd.json.".to_string()
+            }
+        );
+    }
+    Ok(dir)
+}
+
+/// Returns the directory git packs are vendored into.
+pub fn packs_path(llmd: &Path) -> PathBuf {
+    llmd.join("packs")
+}
+
+/// Returns the directory holding `llmd compose --snapshot` output.
+pub fn snapshots_path(llmd: &Path) -> PathBuf {
+    llmd.join("snapshots")
+}
+
+/// Returns the layer a file belongs to, or `None` if it is outside every layer.
+/// Vendored packs live inside the nearest `.llmd/`, so the deepest match wins.
+pub fn layer_of(llmd: &Path, file: &Path) -> Option<Layer> {
+    layers(llmd)
+        .ok()?
+        .into_iter()
+        .filter(|layer| file.starts_with(&layer.dir))
+        .max_by_key(|layer| layer.dir.components().count())
+}
+
+/// Returns a file's path inside its layer (with `/` separators), qualified
+/// with its layer as described in [`Layer::qualify`].
+pub fn layer_path(llmd: &Path, file: &Path) -> String {
+    let layer = layer_of(llmd, file);
+    let base = layer.as_ref().map_or(llmd, |l| l.dir.as_path());
+    let rel = file
+        .strip_prefix(base)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .replace('\\', "/");
+    match layer {
+        Some(layer) => layer.qualify(&rel),
+        None => rel,
+    }
+}
+
+/// Resolves a user-supplied file name (`auth-flow`, `notes/api.md`,
+/// `catme`, `<layer>:name` for a specific layer or `pack:<name> > file` /
+/// `pack:<name>/file` for a pack) to a file, searching workspace layers nearest first. Tries the name
+/// as given, with `.md` appended, and inside `imported/`.
+pub fn find_file(llmd: &Path, name: &str) -> Result<Option<PathBuf>> {
+    let name = &pack_label(name);
+    let layers = layers(llmd)?;
+    let named = |prefix: &str, pack: bool| -> Vec<&Layer> {
+        layers
+            .iter()
+            .filter(|l| l.pack == pack && l.name.as_deref() == Some(prefix.trim()))
+            .collect()
+    };
+    let (layers, name): (Vec<&Layer>, &str) = if let Some(rest) = name.strip_prefix("pack:")
+        && let Some((pack, file)) = rest.split_once('>')
+    {
+        (named(pack, true), file.trim())
+    } else if let Some((prefix, rest)) = name.split_once(':')
+        && !named(prefix, false).is_empty()
+    {
+        (named(prefix, false), rest)
+    } else {
+        (layers.iter().filter(|l| !l.pack).collect(), name)
+    };
+    let name = if name == "catme" { "catme.md" } else { name };
+
+    for layer in layers {
+        for candidate in [
+            layer.dir.join(name),
+            layer.dir.join(format!("{name}.md")),
+            layer.dir.join("imported").join(name),
+        ] {
+            if candidate.is_file() {
+                return Ok(Some(candidate));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Rewrites the whitespace-free form `pack:<name>/file` to the label form
+/// `pack:<name> > file`; other names are returned unchanged.
+pub fn pack_label(name: &str) -> String {
+    match name.strip_prefix("pack:") {
+        Some(rest) if !rest.contains('>') => match rest.split_once('/') {
+            Some((pack, file)) => format!("pack:{pack} > {file}"),
+            None => name.to_string(),
+        },
+        _ => name.to_string(),
+    }
+}
+
+/// Lists the `.md` files of every layer (see [`list_all_files`]), nearest
+/// layer first, then packs. A workspace file is left out when a nearer layer
+/// has one at the same relative path; packs are never shadowed.
+pub fn list_layered_files(llmd: &Path) -> Result<Vec<PathBuf>> {
+    let mut seen = std::collections::HashSet::new();
+    let mut files = Vec::new();
+    for layer in layers(llmd)? {
+        for file in list_all_files(&layer.dir) {
+            if layer.pack {
+                files.push(file);
+                continue;
+            }
+            // Vendored packs are listed as their own layers; snapshots are
+            // composed output, not knowledge.
+            if file.starts_with(packs_path(&layer.dir))
+                || file.starts_with(snapshots_path(&layer.dir))
+            {
+                continue;
+            }
+            let rel = file.strip_prefix(&layer.dir).unwrap_or(&file).to_path_buf();
+            if seen.insert(rel) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
 /// Returns the path to `catme.md` inside the given `.llmd/` directory.
 pub fn catme_path(llmd: &Path) -> PathBuf {
     llmd.join("catme.md")
 }
 
-/// Returns the path to `issues/` inside the given `.llmd/` directory.
+/// Returns the path to the issue tracker directory (`paths.issues`, default `issues/`).
 pub fn issues_path(llmd: &Path) -> PathBuf {
-    llmd.join("issues")
+    llmd.join(config_or_default(llmd).paths.issues)
+}
+
+/// Returns the path to the label-to-topics mapping (`paths.context_mappings`).
+pub fn context_mappings_path(llmd: &Path) -> PathBuf {
+    llmd.join(config_or_default(llmd).paths.context_mappings)
+}
+
+/// Returns true if `rel` (a path relative to `.llmd/`, using `/` separators)
+/// matches the glob `pattern`.
+///
+/// Supports `*` (within one path component), `**` (across components) and `?`.
+/// A pattern without `/` is matched against the file name only; a pattern
+/// ending in `/` matches everything under that directory.
+pub fn glob_match(pattern: &str, rel: &str) -> bool {
+    if let Some(dir) = pattern.strip_suffix('/') {
+        return glob_match(&format!("{dir}/**"), rel);
+    }
+    let target = if pattern.contains('/') {
+        rel
+    } else {
+        rel.rsplit('/').next().unwrap_or(rel)
+    };
+
+    let mut re = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex_lite::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    regex_lite::Regex::new(&re).is_ok_and(|r| r.is_match(target))
 }
 
 /// Lists all `.md` files in all subdirectories of the `.llmd/` directory, recursively.
@@ -47,3 +462,28 @@ pub fn list_all_files(llmd: &Path) -> Vec<PathBuf> {
         .map(|e| e.path().to_path_buf())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("drafts/", "drafts/wip.md"));
+        assert!(glob_match("drafts/*.md", "drafts/wip.md"));
+        assert!(!glob_match("drafts/*.md", "drafts/old/wip.md"));
+        assert!(glob_match("**/wip.md", "drafts/old/wip.md"));
+        assert!(glob_match("*.draft.md", "notes/api.draft.md"));
+        assert!(glob_match("imported/CLAUDE.md", "imported/CLAUDE.md"));
+        assert!(!glob_match("imported/CLAUDE.md", "imported/CLAUDExmd"));
+        assert!(glob_match("file?.md", "file1.md"));
+    }
+
+    #[test]
+    fn test_pack_label() {
+        assert_eq!(pack_label("pack:org/security"), "pack:org > security");
+        assert_eq!(pack_label("pack:org/std/logging"), "pack:org > std/logging");
+        assert_eq!(pack_label("pack:org > security"), "pack:org > security");
+        assert_eq!(pack_label("conventions"), "conventions");
+    }
+}
@@ -1,15 +1,27 @@
 mod commands;
+mod config;
+mod diff;
 mod discovery;
+#[cfg(test)]
+mod fixture;
 mod issues;
 mod llmd_dir;
 mod markdown;
+mod since;
+mod tokenizer;
+mod topic;
+mod transclude;
+mod usage;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 use commands::{
-    bootstrap::BootstrapArgs, build::BuildArgs, compose::ComposeArgs, index::IndexArgs,
-    init::InitArgs, issue::IssueArgs, read::ReadArgs, search::SearchArgs, serve::ServeArgs,
+    bootstrap::BootstrapArgs, build::BuildArgs, compose::ComposeArgs, config::ConfigArgs,
+    index::IndexArgs, init::InitArgs, issue::IssueArgs, pack::PackArgs, read::ReadArgs,
+    search::SearchArgs, serve::ServeArgs, snapshot::SnapshotArgs, stats::StatsArgs,
+    usage::UsageArgs, watch::WatchArgs,
 };
 
 #[derive(Parser)]
@@ -21,6 +33,15 @@ use commands::{
     version
 )]
 struct Cli {
+    /// Use this .llmd/ directory (or the .llmd/ inside it) instead of searching for one.
+    /// Overrides the LLMD_DIR environment variable
+    #[arg(long, global = true, value_name = "DIR")]
+    llmd_dir: Option<PathBuf>,
+
+    /// Search for .llmd/ upward from this directory instead of the current directory
+    #[arg(long, global = true, value_name = "DIR")]
+    root: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -39,16 +60,32 @@ enum Command {
     Compose(ComposeArgs),
     /// Search for text across all .llmd/ files
     Search(SearchArgs),
+    /// Report token counts per file and section, headingless files and always-included cost
+    Stats(StatsArgs),
     /// Generate an mdbook from .llmd/ and serve it locally
     Serve(ServeArgs),
     /// Build a static mdbook site from .llmd/
     Build(BuildArgs),
-    /// Issue tracker: init, new, list, show, update, ready, tree, mentions
+    /// Watch .llmd/ and regenerate the mdbook tree and configured generators on change
+    Watch(WatchArgs),
+    /// Issue tracker: init, new, list, show, update, close, ready, claim, release, handoff, comment, comments, log, activity, tree, mentions, doctor, renumber
     Issue(IssueArgs),
+    /// Show, get or set project configuration in .llmd/llmd.json
+    Config(ConfigArgs),
+    /// Add, update or list external knowledge packs
+    Pack(PackArgs),
+    /// List, compare or replay composed-context snapshots (`llmd compose --snapshot`)
+    Snapshot(SnapshotArgs),
+    /// Report on the opt-in usage log: sections used, activity by day, unused sections
+    Usage(UsageArgs),
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    llmd_dir::select(llmd_dir::Selection {
+        llmd_dir: cli.llmd_dir,
+        root: cli.root,
+    });
 
     match cli.command {
         Command::Init(args) => commands::init::run(args),
@@ -57,8 +94,14 @@ fn main() -> Result<()> {
         Command::Index(args) => commands::index::run(args),
         Command::Compose(args) => commands::compose::run(args),
         Command::Search(args) => commands::search::run(args),
+        Command::Stats(args) => commands::stats::run(args),
         Command::Serve(args) => commands::serve::run(args),
         Command::Build(args) => commands::build::run(args),
+        Command::Watch(args) => commands::watch::run(args),
         Command::Issue(args) => commands::issue::run(args),
+        Command::Config(args) => commands::config::run(args),
+        Command::Pack(args) => commands::pack::run(args),
+        Command::Snapshot(args) => commands::snapshot::run(args),
+        Command::Usage(args) => commands::usage::run(args),
     }
 }
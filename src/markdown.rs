@@ -3,6 +3,8 @@
 //! Uses a line-oriented approach — fast and simple for the structured markdown
 //! that .llmd/ files are expected to contain.
 
+use std::ops::Range;
+
 /// Extracts the content of the first H2 or H3 section whose heading text
 /// contains `section` as a case-insensitive substring. Returns the raw
 /// markdown text of that section, stopping at the next heading of equal or
@@ -35,6 +37,130 @@ pub fn extract_section(source: &str, section: &str) -> Option<String> {
     start_line.map(|start| lines[start..].join("\n"))
 }
 
+/// Like [`extract_section`], but first looks for a heading whose slug (see
+/// [`heading_slug`]) equals `anchor` exactly. Falls back to the substring
+/// match so that `#Error Handling` and `#error-handling` both work.
+pub fn extract_anchor(source: &str, anchor: &str) -> Option<String> {
+    let wanted = heading_slug(anchor);
+    let lines: Vec<&str> = source.lines().collect();
+    let mut start_line: Option<usize> = None;
+    let mut target_depth: usize = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let depth = heading_depth(line);
+        if depth == 0 {
+            continue;
+        }
+        if start_line.is_none() && heading_slug(line.trim_start_matches('#')) == wanted {
+            start_line = Some(i);
+            target_depth = depth;
+        } else if let Some(start) = start_line
+            && depth <= target_depth
+        {
+            return Some(lines[start..i].join("\n"));
+        }
+    }
+
+    match start_line {
+        Some(start) => Some(lines[start..].join("\n")),
+        None => extract_section(source, anchor),
+    }
+}
+
+/// Converts heading text to a GitHub-style anchor slug: lowercase, runs of
+/// whitespace and hyphens become a single `-`, other punctuation is dropped.
+pub fn heading_slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-') && !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// A heading and the markdown it governs.
+pub struct Section {
+    pub depth: usize,
+    pub heading: String,
+    /// The heading line and everything up to the next heading of equal or
+    /// higher level (so an H2's text includes its H3 subsections).
+    pub text: String,
+}
+
+/// Splits `source` into one [`Section`] per heading, in document order.
+pub fn sections(source: &str) -> Vec<Section> {
+    let lines: Vec<&str> = source.lines().collect();
+    let headings: Vec<(usize, usize)> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| (i, heading_depth(line)))
+        .filter(|&(_, depth)| depth > 0)
+        .collect();
+
+    headings
+        .iter()
+        .enumerate()
+        .map(|(n, &(start, depth))| {
+            let end = headings[n + 1..]
+                .iter()
+                .find(|&&(_, d)| d <= depth)
+                .map_or(lines.len(), |&(i, _)| i);
+            Section {
+                depth,
+                heading: lines[start].trim_start_matches('#').trim().to_string(),
+                text: lines[start..end].join("\n"),
+            }
+        })
+        .collect()
+}
+
+/// Returns the first sentence of a section's prose as a one-line preview,
+/// skipping headings, code blocks, HTML comments and blank lines. Long
+/// sentences are cut at `max_chars` with an ellipsis.
+pub fn first_sentence(text: &str, max_chars: usize) -> Option<String> {
+    let mut in_fence = false;
+    let mut paragraph = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            if !paragraph.is_empty() {
+                break;
+            }
+            continue;
+        }
+        if in_fence || heading_depth(line) > 0 || trimmed.starts_with("<!--") {
+            continue;
+        }
+        if trimmed.is_empty() {
+            if !paragraph.is_empty() {
+                break;
+            }
+            continue;
+        }
+        paragraph.push(trimmed.trim_start_matches(['>', '-', '*', ' ']));
+    }
+    let paragraph = paragraph.join(" ");
+    if paragraph.is_empty() {
+        return None;
+    }
+
+    let end = paragraph
+        .match_indices(['.', '!', '?'])
+        .map(|(i, _)| i + 1)
+        .find(|&i| paragraph[i..].is_empty() || paragraph[i..].starts_with(' '))
+        .unwrap_or(paragraph.len());
+    let sentence = &paragraph[..end];
+    if sentence.chars().count() <= max_chars {
+        return Some(sentence.to_string());
+    }
+    let cut: String = sentence.chars().take(max_chars.saturating_sub(1)).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
 /// Returns a list of all headings in `source` as `(depth, text)` pairs.
 pub fn list_headings(source: &str) -> Vec<(usize, String)> {
     source
@@ -50,6 +176,62 @@ pub fn list_headings(source: &str) -> Vec<(usize, String)> {
         .collect()
 }
 
+/// Returns the section references declared in `source` with
+/// `<!-- llmd:requires file#section, other#section -->` markers, in order.
+///
+/// References may be separated by commas or whitespace. A reference without a
+/// file part (`#section`) points into the same file as the marker.
+pub fn requires_markers(source: &str) -> Vec<String> {
+    let mut refs = Vec::new();
+    for line in source.lines() {
+        let Some(rest) = line.trim().strip_prefix("<!--") else {
+            continue;
+        };
+        let Some(body) = rest.trim_start().strip_prefix("llmd:requires") else {
+            continue;
+        };
+        let body = body.trim_end().trim_end_matches("-->");
+        refs.extend(
+            body.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|r| !r.is_empty())
+                .map(str::to_string),
+        );
+    }
+    refs
+}
+
+/// Returns the source paths listed in catme.md's `## Context Map` section:
+/// the first backticked path of each `- \`src/auth/\` → [doc](doc.md)` item.
+/// Lines inside HTML comments (such as the `llmd init` placeholder) are ignored.
+pub fn context_map_paths(catme: &str) -> Vec<String> {
+    let Some(section) = extract_section(catme, "Context Map") else {
+        return Vec::new();
+    };
+    let mut paths = Vec::new();
+    let mut in_comment = false;
+    for line in section.lines() {
+        let line = line.trim();
+        if in_comment {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+        if line.starts_with("<!--") {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+        let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) else {
+            continue;
+        };
+        if let Some(rest) = item.trim_start().strip_prefix('`')
+            && let Some((path, _)) = rest.split_once('`')
+            && !path.is_empty()
+        {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
 /// Estimates the number of tokens in `text` using the heuristic of 1 token per
 /// 4 characters (a conservative approximation for English prose and code).
 pub fn estimate_tokens(text: &str) -> usize {
@@ -67,6 +249,91 @@ pub fn window(source: &str, start: usize, end: usize) -> String {
         .join("\n")
 }
 
+/// Returns the byte ranges of `source` that are code: fenced blocks (``` or
+/// `~~~`, fence lines included) and inline code spans. An unclosed fence runs
+/// to the end of `source`; code spans do not cross line breaks.
+pub fn code_ranges(source: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    // The open fence's character, length and start offset.
+    let mut fence: Option<(u8, usize, usize)> = None;
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        match (fence, fence_marker(line)) {
+            (Some((c, n, open)), Some((mc, mn)))
+                if mc == c && mn >= n && line.trim().bytes().all(|b| b == c) =>
+            {
+                ranges.push(open..offset);
+                fence = None;
+            }
+            (Some(_), _) => {}
+            (None, Some((c, n))) => fence = Some((c, n, start)),
+            (None, None) => ranges.extend(
+                code_spans(line)
+                    .into_iter()
+                    .map(|r| start + r.start..start + r.end),
+            ),
+        }
+    }
+    if let Some((_, _, open)) = fence {
+        ranges.push(open..source.len());
+    }
+    ranges
+}
+
+/// The fence character and length if `line` opens or closes a fenced block.
+fn fence_marker(line: &str) -> Option<(u8, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let c = *trimmed
+        .as_bytes()
+        .first()
+        .filter(|&&c| c == b'`' || c == b'~')?;
+    let n = trimmed.bytes().take_while(|&b| b == c).count();
+    (n >= 3).then_some((c, n))
+}
+
+/// Code spans in `line`: a run of backticks up to the next run of the same
+/// length. A run without a match is literal.
+fn code_spans(line: &str) -> Vec<Range<usize>> {
+    let bytes = line.as_bytes();
+    let run = |at: usize| bytes[at..].iter().take_while(|&&b| b == b'`').count();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'`' {
+            i += 1;
+            continue;
+        }
+        let open = run(i);
+        let mut j = i + open;
+        let mut close = None;
+        while j < bytes.len() {
+            if bytes[j] != b'`' {
+                j += 1;
+                continue;
+            }
+            let n = run(j);
+            if n == open {
+                close = Some(j + n);
+                break;
+            }
+            j += n;
+        }
+        match close {
+            Some(end) => {
+                spans.push(i..end);
+                i = end;
+            }
+            None => i += open,
+        }
+    }
+    spans
+}
+
 /// Returns the heading depth of a line (1 for `#`, 2 for `##`, etc.),
 /// or 0 if the line is not a heading.
 fn heading_depth(line: &str) -> usize {
@@ -100,6 +367,69 @@ mod tests {
         assert!(!result.contains("beta content"));
     }
 
+    #[test]
+    fn test_heading_slug() {
+        assert_eq!(heading_slug("Error Handling"), "error-handling");
+        assert_eq!(heading_slug("  Stdout vs. Stderr "), "stdout-vs-stderr");
+        assert_eq!(heading_slug("`llmd read` — flags"), "llmd-read-flags");
+    }
+
+    #[test]
+    fn test_extract_anchor() {
+        let md = "## Error Handling\n\nuse anyhow\n\n## Errors Elsewhere\n\nother\n";
+        let result = extract_anchor(md, "error-handling").unwrap();
+        assert!(result.contains("use anyhow"));
+        assert!(!result.contains("other"));
+        let fallback = extract_anchor(md, "Elsewhere").unwrap();
+        assert!(fallback.contains("other"));
+        assert!(extract_anchor(md, "missing").is_none());
+    }
+
+    #[test]
+    fn test_requires_markers() {
+        let md = "## Session Validation\n\n<!-- llmd:requires auth-flow#token-format -->\n\
+                  text\n<!--llmd:requires #a, b#c -->\n<!-- other comment -->\n";
+        assert_eq!(
+            requires_markers(md),
+            vec!["auth-flow#token-format", "#a", "b#c"]
+        );
+    }
+
+    #[test]
+    fn test_context_map_paths() {
+        let md = "## Context Map\n\n<!-- Example:\n- `src/x/` → [x.md](x.md)\n-->\n\
+                  - `src/auth/` → [auth.md](auth.md)\n- `Cargo.toml` → [build.md](build.md)\n\
+                  - no path here\n\n## Next\n\n- `src/other/`\n";
+        assert_eq!(context_map_paths(md), vec!["src/auth/", "Cargo.toml"]);
+        assert!(context_map_paths("# Empty\n").is_empty());
+    }
+
+    #[test]
+    fn test_sections() {
+        let md = "# Top\n\n## A\n\na\n\n### A1\n\na1\n\n## B\n\nb\n";
+        let all = sections(md);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[1].heading, "A");
+        assert!(all[1].text.contains("a1"));
+        assert!(!all[1].text.contains("## B"));
+        assert_eq!(all[2].depth, 3);
+        assert_eq!(all[3].text, "## B\n\nb");
+    }
+
+    #[test]
+    fn test_first_sentence() {
+        let md = "## Errors\n\n<!-- note -->\nUse `anyhow` everywhere. Never panic.\n";
+        assert_eq!(
+            first_sentence(md, 80).as_deref(),
+            Some("Use `anyhow` everywhere.")
+        );
+        assert_eq!(first_sentence("## Only\n\n```sh\nls\n```\n", 80), None);
+        assert_eq!(
+            first_sentence("A very long sentence", 8).as_deref(),
+            Some("A very…")
+        );
+    }
+
     #[test]
     fn test_estimate_tokens() {
         assert_eq!(estimate_tokens("1234"), 1);
@@ -116,6 +446,21 @@ mod tests {
         assert_eq!(headings[2], (3, "Deep".to_string()));
     }
 
+    #[test]
+    fn test_code_ranges() {
+        let md = "a `x` b ``c ` d`` `open\n```rust\nfn f() {}\n```\n~~~\nunclosed\n";
+        let code: Vec<&str> = code_ranges(md).into_iter().map(|r| &md[r]).collect();
+        assert_eq!(
+            code,
+            vec![
+                "`x`",
+                "``c ` d``",
+                "```rust\nfn f() {}\n```\n",
+                "~~~\nunclosed\n"
+            ]
+        );
+    }
+
     #[test]
     fn test_window() {
         let text = "a\nb\nc\nd\ne";
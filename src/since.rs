@@ -0,0 +1,205 @@
+//! `--since` support: what changed in .llmd/ since a git revision or snapshot.
+//!
+//! A [`Baseline`] yields the earlier content of a file — from `git show
+//! <rev>:<path>`, or from the copies `llmd compose --snapshot` keeps of its
+//! input files. [`changed_parts`] then compares the two versions section by
+//! section, so `read` and `compose` can emit only what an agent has not seen.
+
+use anyhow::{Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::snapshot::{self, Manifest};
+use crate::{llmd_dir, markdown, topic, transclude};
+
+/// The point in time `--since` compares against.
+pub enum Baseline {
+    Git {
+        rev: String,
+        /// The repository's top-level directory.
+        root: PathBuf,
+    },
+    Snapshot {
+        id: String,
+        manifest: Box<Manifest>,
+    },
+}
+
+impl Baseline {
+    /// Resolves a snapshot id (or unique prefix) or, failing that, a git revision.
+    pub fn resolve(llmd: &Path, reference: &str) -> Result<Self> {
+        if let Ok(id) = snapshot::resolve(llmd, reference) {
+            let manifest = Box::new(snapshot::load(llmd, &id)?.0);
+            return Ok(Self::Snapshot { id, manifest });
+        }
+        let project = llmd.parent().unwrap_or(llmd);
+        let commit = format!("{reference}^{{commit}}");
+        let verify = git(project, &["rev-parse", "--verify", "--quiet", &commit]);
+        let toplevel = git(project, &["rev-parse", "--show-toplevel"]);
+        match (verify, toplevel) {
+            (Some(_), Some(root)) => Ok(Self::Git {
+                rev: reference.to_string(),
+                root: PathBuf::from(root.trim()),
+            }),
+            _ => bail!(
+                "\"{reference}\" is neither a snapshot (see `llmd snapshot list`) nor a git revision"
+            ),
+        }
+    }
+
+    /// How to refer to the baseline in messages and diff headers.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Git { rev, .. } => rev,
+            Self::Snapshot { id, .. } => id,
+        }
+    }
+
+    /// The file's raw content at the baseline, or `None` if it did not exist
+    /// there (or, for a snapshot, was not one of its inputs).
+    pub fn content(&self, llmd: &Path, file: &Path) -> Option<String> {
+        match self {
+            Self::Git { rev, root } => {
+                let rel = file
+                    .strip_prefix(root)
+                    .ok()?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                git(root, &["show", &format!("{rev}:{rel}")])
+            }
+            Self::Snapshot { id, manifest } => {
+                let hash = manifest.files.get(&llmd_dir::layer_path(llmd, file))?;
+                fs::read_to_string(snapshot::blob_path(llmd, id, hash)).ok()
+            }
+        }
+    }
+
+    /// The file's body at the baseline, prepared like `topic::load` does:
+    /// frontmatter stripped and includes resolved against the baseline's
+    /// versions of the included files, so a change to an included file
+    /// shows up too. If that fails (say, a snapshot taken before included
+    /// files were recorded), the body is compared unexpanded.
+    pub fn body(&self, llmd: &Path, file: &Path) -> Option<String> {
+        let content = self.content(llmd, file)?;
+        let body = topic::split(&content).1;
+        let read = |path: &Path| self.content(llmd, path);
+        Some(
+            transclude::resolve_with(llmd, file, body, &read)
+                .map_or_else(|_| body.to_string(), |(text, _)| text),
+        )
+    }
+}
+
+/// Runs git in `dir`, returning stdout on success.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A heading and the text directly under it, up to the next heading of any level.
+#[derive(Debug, PartialEq)]
+pub struct Part {
+    /// Headings from the top of the document down to this one; empty for the
+    /// text before the first heading.
+    pub path: Vec<String>,
+    pub text: String,
+}
+
+impl Part {
+    /// `Heading > Subheading`, or `(intro)` for text before the first heading.
+    pub fn label(&self) -> String {
+        if self.path.is_empty() {
+            "(intro)".to_string()
+        } else {
+            self.path.join(" > ")
+        }
+    }
+}
+
+/// Splits a body into [`Part`]s in document order. Blank-only intros are dropped.
+pub fn parts(body: &str) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let intro: Vec<&str> = body
+        .lines()
+        .take_while(|l| markdown::list_headings(l).is_empty())
+        .collect();
+    if intro.iter().any(|l| !l.trim().is_empty()) {
+        parts.push(Part {
+            path: Vec::new(),
+            text: intro.join("\n"),
+        });
+    }
+
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    for section in markdown::sections(body) {
+        stack.retain(|(depth, _)| *depth < section.depth);
+        stack.push((section.depth, section.heading));
+        let own: Vec<&str> = section
+            .text
+            .lines()
+            .enumerate()
+            .take_while(|(i, l)| *i == 0 || markdown::list_headings(l).is_empty())
+            .map(|(_, l)| l)
+            .collect();
+        parts.push(Part {
+            path: stack.iter().map(|(_, h)| h.clone()).collect(),
+            text: own.join("\n"),
+        });
+    }
+    parts
+}
+
+/// Parts of `new` that are new or whose text differs from the part with the
+/// same heading path in `old`, and the labels of parts removed since `old`.
+pub fn changed_parts(old: &str, new: &str) -> (Vec<Part>, Vec<String>) {
+    let old = parts(old);
+    let new = parts(new);
+    let removed = old
+        .iter()
+        .filter(|o| !new.iter().any(|n| n.path == o.path))
+        .map(Part::label)
+        .collect();
+    let changed = new
+        .into_iter()
+        .filter(|n| {
+            !old.iter()
+                .any(|o| o.path == n.path && o.text.trim_end() == n.text.trim_end())
+        })
+        .collect();
+    (changed, removed)
+}
+
+/// The text of the section in `old` with the given heading and depth
+/// (subsections included), if there is one.
+pub fn old_section(old: &str, depth: usize, heading: &str) -> Option<String> {
+    markdown::sections(old)
+        .into_iter()
+        .find(|s| s.depth == depth && s.heading == heading)
+        .map(|s| s.text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_parts() {
+        let old = "intro\n\n## A\n\na\n\n### A1\n\none\n\n## B\n\nb\n\n## Gone\n\nx\n";
+        let new = "intro\n\n## A\n\na\n\n### A1\n\nONE\n\n## B\n\nb\n\n## C\n\nc\n";
+        let (changed, removed) = changed_parts(old, new);
+        let labels: Vec<String> = changed.iter().map(Part::label).collect();
+        assert_eq!(labels, vec!["A > A1", "C"]);
+        assert_eq!(changed[0].text, "### A1\n\nONE");
+        assert_eq!(removed, vec!["Gone"]);
+        assert!(changed_parts(new, new).0.is_empty());
+    }
+}
@@ -0,0 +1,308 @@
+//! Token counting: the `len / 4` heuristic or an offline BPE tokenizer.
+//!
+//! The BPE mode reads a vocabulary in tiktoken's text format (one
+//! `<base64 token> <rank>` pair per line, e.g. `cl100k_base.tiktoken`) from
+//! the path in `tokenizer.vocab`. Text is split with a hand-written
+//! equivalent of the cl100k pre-tokenization pattern (regex-lite has no
+//! Unicode classes or lookahead), then each piece is merged by rank.
+
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::TokenizerMode;
+use crate::{llmd_dir, markdown};
+
+/// Counts tokens the way the project's config asks for.
+pub enum Tokenizer {
+    /// `markdown::estimate_tokens`: fast, roughly right for English prose.
+    Heuristic,
+    /// Byte-pair encoding with a loaded vocabulary.
+    Bpe(Bpe),
+}
+
+impl Tokenizer {
+    /// Returns the tokenizer selected by `tokenizer.mode`, loading the vocab if needed.
+    pub fn for_project(llmd: &Path) -> Result<Self> {
+        let config = llmd_dir::config(llmd)?.tokenizer;
+        match config.mode {
+            TokenizerMode::Heuristic => Ok(Self::Heuristic),
+            TokenizerMode::Bpe => {
+                let vocab = config.vocab.unwrap_or_default();
+                let path = llmd.parent().unwrap_or(llmd).join(vocab);
+                let content = fs::read_to_string(&path).with_context(|| {
+                    format!(
+                        "Cannot read tokenizer vocab {} (tokenizer.vocab)",
+                        path.display()
+                    )
+                })?;
+                let bpe = Bpe::from_tiktoken(&content)
+                    .with_context(|| format!("Invalid tokenizer vocab {}", path.display()))?;
+                Ok(Self::Bpe(bpe))
+            }
+        }
+    }
+
+    /// Number of tokens in `text`.
+    pub fn count(&self, text: &str) -> usize {
+        match self {
+            Self::Heuristic => markdown::estimate_tokens(text),
+            Self::Bpe(bpe) => bpe.count(text),
+        }
+    }
+
+    /// Formats a count for display: `~N tokens` for estimates, `N tokens` for exact counts.
+    pub fn describe(&self, count: usize) -> String {
+        match self {
+            Self::Heuristic => format!("~{count} tokens"),
+            Self::Bpe(_) => format!("{count} tokens"),
+        }
+    }
+}
+
+/// A byte-pair encoder: token bytes mapped to their merge rank.
+pub struct Bpe {
+    ranks: HashMap<Vec<u8>, u32>,
+}
+
+impl Bpe {
+    /// Parses a tiktoken vocabulary: `<base64 bytes> <rank>` per line.
+    pub fn from_tiktoken(content: &str) -> Result<Self> {
+        let mut ranks = HashMap::new();
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (token, rank) = line
+                .split_once(' ')
+                .with_context(|| format!("line {}: expected `<base64> <rank>`", i + 1))?;
+            let bytes = base64_decode(token)
+                .with_context(|| format!("line {}: invalid base64 \"{token}\"", i + 1))?;
+            let rank: u32 = rank
+                .trim()
+                .parse()
+                .with_context(|| format!("line {}: invalid rank \"{rank}\"", i + 1))?;
+            ranks.insert(bytes, rank);
+        }
+        if ranks.is_empty() {
+            bail!("vocabulary is empty");
+        }
+        Ok(Self { ranks })
+    }
+
+    /// Number of tokens in `text`.
+    pub fn count(&self, text: &str) -> usize {
+        pre_tokenize(text)
+            .iter()
+            .map(|piece| self.merge(piece.as_bytes()).len() - 1)
+            .sum()
+    }
+
+    /// Merges a piece's bytes, lowest rank first, and returns the token
+    /// boundaries (`n` tokens give `n + 1` offsets).
+    fn merge(&self, piece: &[u8]) -> Vec<usize> {
+        if self.ranks.contains_key(piece) {
+            return vec![0, piece.len()];
+        }
+        let mut bounds: Vec<usize> = (0..=piece.len()).collect();
+        loop {
+            let best = (0..bounds.len().saturating_sub(2))
+                .filter_map(|i| {
+                    self.ranks
+                        .get(&piece[bounds[i]..bounds[i + 2]])
+                        .map(|&rank| (rank, i))
+                })
+                .min();
+            match best {
+                Some((_, i)) => {
+                    bounds.remove(i + 1);
+                }
+                None => return bounds,
+            }
+        }
+    }
+}
+
+/// Splits text into pieces following the cl100k pattern:
+///
+/// ```text
+/// (?i:'s|'t|'re|'ve|'m|'ll|'d) | [^\r\n\p{L}\p{N}]?\p{L}+ | \p{N}{1,3}
+///   | ?[^\s\p{L}\p{N}]+[\r\n]* | \s*[\r\n]+ | \s+(?!\S) | \s+
+/// ```
+fn pre_tokenize(text: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(text.len(), |&(o, _)| o);
+    let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+    let is_letter = |c: Option<char>| c.is_some_and(char::is_alphabetic);
+    let is_number = |c: Option<char>| c.is_some_and(char::is_numeric);
+    let is_space = |c: Option<char>| c.is_some_and(char::is_whitespace);
+    let is_newline = |c: Option<char>| matches!(c, Some('\r' | '\n'));
+
+    let mut pieces = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = at(i);
+        let start = i;
+
+        if c == Some('\'')
+            && let Some(len) = contraction(&chars[i + 1..])
+        {
+            i += 1 + len;
+        } else if is_letter(c) || (!is_newline(c) && !is_number(c) && is_letter(at(i + 1))) {
+            i += 1;
+            while is_letter(at(i)) {
+                i += 1;
+            }
+        } else if is_number(c) {
+            while i - start < 3 && is_number(at(i)) {
+                i += 1;
+            }
+        } else if !is_space(c) || (c == Some(' ') && at(i + 1).is_some_and(|n| !n.is_whitespace()))
+        {
+            // ` ?[^\s\p{L}\p{N}]+[\r\n]*` — letters and numbers were handled above.
+            if c == Some(' ') {
+                i += 1;
+            }
+            while at(i).is_some_and(|n| !n.is_whitespace() && !n.is_alphabetic() && !n.is_numeric())
+            {
+                i += 1;
+            }
+            while is_newline(at(i)) {
+                i += 1;
+            }
+            if i == start {
+                i += 1;
+            }
+        } else {
+            let mut end = i;
+            while is_space(at(end)) {
+                end += 1;
+            }
+            let last_newline = (i..end).rev().find(|&j| is_newline(at(j)));
+            i = match last_newline {
+                Some(j) => j + 1,
+                None if end == chars.len() || end - i == 1 => end,
+                None => end - 1,
+            };
+        }
+        pieces.push(&text[offset(start)..offset(i)]);
+    }
+    pieces
+}
+
+/// Length in chars of an English contraction suffix (`s`, `re`, …) at the
+/// start of `rest`, matched case-insensitively.
+fn contraction(rest: &[(usize, char)]) -> Option<usize> {
+    let lower: String = rest
+        .iter()
+        .take(2)
+        .map(|&(_, c)| c.to_ascii_lowercase())
+        .collect();
+    ["re", "ve", "ll", "s", "t", "m", "d"]
+        .iter()
+        .find(|s| lower.starts_with(*s))
+        .map(|s| s.len())
+}
+
+/// Decodes standard (padded or unpadded) base64.
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let s = s.trim_end_matches('=');
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut buf = 0u32;
+    let mut bits = 0;
+    for c in s.bytes() {
+        buf = (buf << 6) | u32::from(value(c)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A vocab of all single bytes plus a few merges, in tiktoken format.
+    fn tiny_vocab() -> Bpe {
+        let encode = |bytes: &[u8]| -> String {
+            const ALPHABET: &[u8] =
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+            let mut out = String::new();
+            for chunk in bytes.chunks(3) {
+                let n = chunk.iter().fold(0u32, |acc, &b| (acc << 8) | u32::from(b))
+                    << (8 * (3 - chunk.len()));
+                for i in 0..=chunk.len() {
+                    out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+                }
+            }
+            out
+        };
+        let mut lines: Vec<String> = (0..=255u8)
+            .map(|b| format!("{} {b}", encode(&[b])))
+            .collect();
+        for (i, token) in ["he", "ll", "llo", "hello", " w", " wor", " world"]
+            .iter()
+            .enumerate()
+        {
+            lines.push(format!("{} {}", encode(token.as_bytes()), 256 + i));
+        }
+        Bpe::from_tiktoken(&lines.join("\n")).unwrap()
+    }
+
+    /// Encodes `text` into token ranks, so tests can check merges directly.
+    fn encode(bpe: &Bpe, text: &str) -> Vec<u32> {
+        let mut out = Vec::new();
+        for piece in pre_tokenize(text) {
+            let bytes = piece.as_bytes();
+            let bounds = bpe.merge(bytes);
+            out.extend(
+                bounds
+                    .windows(2)
+                    .filter_map(|w| bpe.ranks.get(&bytes[w[0]..w[1]]).copied()),
+            );
+        }
+        out
+    }
+
+    #[test]
+    fn test_base64_decode() {
+        assert_eq!(base64_decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(base64_decode("IHdvcmxk").unwrap(), b" world");
+        assert_eq!(base64_decode("IQ==").unwrap(), b"!");
+        assert!(base64_decode("a$b").is_none());
+    }
+
+    #[test]
+    fn test_pre_tokenize() {
+        assert_eq!(
+            pre_tokenize("Hello world, it's 12345!\n\n  fn x()"),
+            vec![
+                "Hello", " world", ",", " it", "'s", " ", "123", "45", "!\n\n", " ", " fn", " x",
+                "()"
+            ]
+        );
+        assert_eq!(pre_tokenize("a  \n"), vec!["a", "  \n"]);
+        assert_eq!(pre_tokenize("日本語 text"), vec!["日本語", " text"]);
+    }
+
+    #[test]
+    fn test_bpe_encode() {
+        let bpe = tiny_vocab();
+        assert_eq!(encode(&bpe, "hello world"), vec![259, 262]);
+        assert_eq!(encode(&bpe, "hellx"), vec![256, 257, b'x' as u32]);
+        assert_eq!(bpe.count("hello world!"), 3);
+        assert_eq!(bpe.count(""), 0);
+    }
+}
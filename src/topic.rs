@@ -0,0 +1,233 @@
+//! Topic files: optional YAML frontmatter plus a markdown body.
+//!
+//! Any `.llmd/*.md` file may start with a `---` delimited frontmatter block:
+//!
+//! ```yaml
+//! ---
+//! title: Authentication Flow
+//! summary: How tokens are issued, validated and refreshed.
+//! tags: [auth, security]
+//! owners: [platform-team]
+//! source_paths:
+//!   - src/auth/
+//! priority: high
+//! last_reviewed: 2026-01-15
+//! requires: [conventions#error-handling]
+//! ---
+//! ```
+//!
+//! All keys are optional and unknown keys are ignored. The block is parsed
+//! with `serde_yaml_ng`, like issue frontmatter, so multi-line and quoted
+//! values work; list keys also accept a comma-separated string. A block that
+//! is not a YAML mapping is not frontmatter, and the file is read unchanged.
+
+use anyhow::{Context, Result};
+use serde_yaml_ng::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::{llmd_dir, transclude};
+
+/// Files already warned about by [`load_lenient`], so commands that load a
+/// file more than once warn once.
+static WARNED: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// Metadata from a topic file's frontmatter. Every field is optional.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct TopicMeta {
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    pub tags: Vec<String>,
+    pub owners: Vec<String>,
+    pub source_paths: Vec<String>,
+    pub priority: Option<String>,
+    pub last_reviewed: Option<String>,
+    /// File-level prerequisites (`file#section`), applied to every section in the file.
+    pub requires: Vec<String>,
+}
+
+impl TopicMeta {
+    /// Sort rank for `priority`: high before medium (the default) before low.
+    pub fn priority_rank(&self) -> u8 {
+        match self.priority.as_deref() {
+            Some("high") => 0,
+            Some("low") => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// A topic file with its frontmatter parsed and its body's includes resolved.
+pub struct Topic {
+    pub meta: TopicMeta,
+    pub body: String,
+    /// Files pulled in by `{{#include}}`, in the order they were first read.
+    pub includes: Vec<PathBuf>,
+}
+
+/// Reads a topic file, parses its frontmatter and resolves `{{#include}}`
+/// directives in the body.
+pub fn load(llmd: &Path, path: &Path) -> Result<Topic> {
+    read(llmd, path, false)
+}
+
+/// Like [`load`], but a file whose includes cannot be resolved is returned
+/// with its body unexpanded, after a warning naming it. Commands that read
+/// every topic use this so one broken directive does not stop them.
+pub fn load_lenient(llmd: &Path, path: &Path) -> Result<Topic> {
+    read(llmd, path, true)
+}
+
+fn read(llmd: &Path, path: &Path, lenient: bool) -> Result<Topic> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let (meta, body) = split(&content);
+    let (body, includes) = match transclude::resolve(llmd, path, body) {
+        Ok(resolved) => resolved,
+        Err(e) if lenient => {
+            let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+            if warned.insert(path.to_path_buf()) {
+                eprintln!(
+                    "Warning: {e:#}; using {} without its includes",
+                    llmd_dir::layer_path(llmd, path)
+                );
+            }
+            (body.to_string(), Vec::new())
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(Topic {
+        meta,
+        body,
+        includes,
+    })
+}
+
+/// Splits `content` into its frontmatter metadata and the body that follows.
+///
+/// The block runs from a leading `---` line to the next `---` line (either
+/// line ending works) and must parse as a YAML mapping. Otherwise — no block,
+/// or one that is not a mapping, such as a thematic break followed by prose —
+/// the result is default metadata and the unchanged content.
+pub fn split(content: &str) -> (TopicMeta, &str) {
+    let unchanged = (TopicMeta::default(), content);
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return unchanged;
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            let body = &rest[offset + line.len()..];
+            return match parse_meta(&rest[..offset]) {
+                Some(meta) => (meta, body.trim_start_matches(['\r', '\n'])),
+                None => unchanged,
+            };
+        }
+        offset += line.len();
+    }
+    unchanged
+}
+
+/// Parses a frontmatter block, or `None` if it is not a YAML mapping.
+fn parse_meta(fm: &str) -> Option<TopicMeta> {
+    let Ok(Value::Mapping(map)) = serde_yaml_ng::from_str::<Value>(fm) else {
+        return None;
+    };
+    let scalar = |key: &str| map.get(key).and_then(scalar);
+    let list = |key: &str| map.get(key).map(list).unwrap_or_default();
+    Some(TopicMeta {
+        title: scalar("title"),
+        summary: scalar("summary"),
+        tags: list("tags"),
+        owners: list("owners"),
+        source_paths: list("source_paths"),
+        priority: scalar("priority").map(|v| v.to_lowercase()),
+        last_reviewed: scalar("last_reviewed"),
+        requires: list("requires"),
+    })
+}
+
+/// A string, number or boolean as a trimmed string; `None` for anything
+/// else, including null and the empty string.
+fn scalar(value: &Value) -> Option<String> {
+    let s = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return None,
+    };
+    Some(s).filter(|s| !s.is_empty())
+}
+
+/// A YAML list, or a scalar split on commas.
+fn list(value: &Value) -> Vec<String> {
+    match value {
+        Value::Sequence(items) => items.iter().filter_map(scalar).collect(),
+        other => scalar(other)
+            .map(|s| {
+                s.split(',')
+                    .map(|item| item.trim().to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let md = "---\ntitle: \"Auth Flow\"\nsummary: Tokens.\ntags: [auth, 'security']\n\
+                  owners:\n  - alice\n  - bob\npriority: High\nunknown: x\n---\n\n# Auth\n";
+        let (meta, body) = split(md);
+        assert_eq!(meta.title.as_deref(), Some("Auth Flow"));
+        assert_eq!(meta.summary.as_deref(), Some("Tokens."));
+        assert_eq!(meta.tags, vec!["auth", "security"]);
+        assert_eq!(meta.owners, vec!["alice", "bob"]);
+        assert_eq!(meta.priority.as_deref(), Some("high"));
+        assert_eq!(meta.priority_rank(), 0);
+        assert_eq!(body, "# Auth\n");
+
+        let md = "---\nsummary: >\n  Tokens are issued\n  and refreshed.\ntags: auth, sso\n\
+                  last_reviewed: 2026-01-15\nextra:\n  nested: [1, 2]\n---\nbody";
+        let (meta, body) = split(md);
+        assert_eq!(
+            meta.summary.as_deref(),
+            Some("Tokens are issued and refreshed.")
+        );
+        assert_eq!(meta.tags, vec!["auth", "sso"]);
+        assert_eq!(meta.last_reviewed.as_deref(), Some("2026-01-15"));
+        assert_eq!(body, "body");
+    }
+
+    #[test]
+    fn test_split_without_frontmatter() {
+        let md = "# Title\n\n---\n\ntext\n";
+        let (meta, body) = split(md);
+        assert_eq!(meta, TopicMeta::default());
+        assert_eq!(body, md);
+
+        // A thematic break, prose and a later break are content, not frontmatter.
+        let md = "---\nSome intro prose.\n---\n# Title\n";
+        assert_eq!(split(md), (TopicMeta::default(), md));
+        let md = "---\n- a\n- b\n---\nbody";
+        assert_eq!(split(md), (TopicMeta::default(), md));
+    }
+
+    #[test]
+    fn test_split_crlf() {
+        let md = "---\r\ntitle: Auth\r\ntags: [a, b]\r\n---\r\n\r\n# Auth\r\n";
+        let (meta, body) = split(md);
+        assert_eq!(meta.title.as_deref(), Some("Auth"));
+        assert_eq!(meta.tags, vec!["a", "b"]);
+        assert_eq!(body, "# Auth\r\n");
+    }
+}
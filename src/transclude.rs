@@ -0,0 +1,311 @@
+//! Resolves `{{#include file.md#section}}` directives in .llmd/ files.
+//!
+//! Mirrors mdbook's include syntax. The path is relative to the including file
+//! (the `.md` extension may be omitted) and the optional `#anchor` selects one
+//! section by heading slug, e.g. `conventions.md#error-handling`. Includes nest;
+//! cycles and missing targets are reported as errors, and so are targets
+//! outside the layer (`.llmd/` or pack) of the including file. Directives in
+//! code spans and fenced code blocks are left as they are; elsewhere, prefix
+//! one with a backslash (`\{{#include …}}`) to emit it literally.
+//! Frontmatter of included files is dropped.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{llmd_dir, markdown, topic};
+
+const DIRECTIVE: &str = "{{#include";
+
+/// Expands every include directive in `content`, read from `path`,
+/// recursively. Returns the expanded text and the files it read, in the
+/// order they were first read.
+///
+/// `llmd` is used to shorten paths in error messages and to find the layer
+/// that included files must stay inside.
+pub fn resolve(llmd: &Path, path: &Path, content: &str) -> Result<(String, Vec<PathBuf>)> {
+    resolve_with(llmd, path, content, &|p| fs::read_to_string(p).ok())
+}
+
+/// Like [`resolve`], but reads included files through `read`, which returns
+/// `None` for a file that does not exist. `--since` uses it to expand an old
+/// version of a file against the included files as they were then.
+pub fn resolve_with(
+    llmd: &Path,
+    path: &Path,
+    content: &str,
+    read: &dyn Fn(&Path) -> Option<String>,
+) -> Result<(String, Vec<PathBuf>)> {
+    let path = normalise(path);
+    // The deepest layer holding the file: vendored packs live inside `.llmd/`.
+    let root = llmd_dir::layers(llmd)
+        .unwrap_or_default()
+        .iter()
+        .map(|layer| normalise(&layer.dir))
+        .filter(|dir| path.starts_with(dir))
+        .max_by_key(|dir| dir.components().count())
+        .unwrap_or_else(|| normalise(llmd));
+    let mut resolver = Resolver {
+        llmd,
+        read,
+        root,
+        stack: vec![(path.clone(), None)],
+        files: Vec::new(),
+    };
+    let text = resolver.expand(&path, content)?;
+    Ok((text, resolver.files))
+}
+
+/// An entry on the include stack: the file and the anchor taken from it.
+type Frame = (PathBuf, Option<String>);
+
+struct Resolver<'a> {
+    llmd: &'a Path,
+    read: &'a dyn Fn(&Path) -> Option<String>,
+    /// Included files must be inside this directory.
+    root: PathBuf,
+    stack: Vec<Frame>,
+    /// Every file read so far, once each.
+    files: Vec<PathBuf>,
+}
+
+impl Resolver<'_> {
+    fn expand(&mut self, file: &Path, content: &str) -> Result<String> {
+        if !content.contains(DIRECTIVE) {
+            return Ok(content.to_string());
+        }
+
+        // Directives inside code spans and fenced blocks are shown, not run.
+        let code = markdown::code_ranges(content);
+        let mut out = String::with_capacity(content.len());
+        // `content[..copied]` is in `out`; the next directive is searched from `from`.
+        let mut copied = 0;
+        let mut from = 0;
+
+        while let Some(found) = content[from..].find(DIRECTIVE) {
+            let pos = from + found;
+            from = pos + DIRECTIVE.len();
+            if code.iter().any(|r| r.contains(&pos)) {
+                continue;
+            }
+            let before = &content[copied..pos];
+            if let Some(escaped) = before.strip_suffix('\\') {
+                out.push_str(escaped);
+                out.push_str(DIRECTIVE);
+                copied = from;
+                continue;
+            }
+            out.push_str(before);
+
+            let Some(end) = content[from..].find("}}") else {
+                bail!(
+                    "Unterminated include directive in {}: missing `}}}}`",
+                    display(self.llmd, file)
+                );
+            };
+            let target = content[from..from + end].trim();
+            out.push_str(&self.include(file, target)?);
+            copied = from + end + 2;
+            from = copied;
+        }
+        out.push_str(&content[copied..]);
+
+        Ok(out)
+    }
+
+    fn include(&mut self, from: &Path, target: &str) -> Result<String> {
+        let llmd = self.llmd;
+        if target.is_empty() {
+            bail!("Empty include directive in {}", display(llmd, from));
+        }
+        let (rel, anchor) = match target.split_once('#') {
+            Some((p, a)) => (p, Some(a.to_string())),
+            None => (target, None),
+        };
+
+        let base = from.parent().unwrap_or(Path::new("."));
+        let (path, content) = if rel.is_empty() {
+            let content =
+                (self.read)(from).with_context(|| format!("Cannot read {}", from.display()))?;
+            (from.to_path_buf(), content)
+        } else {
+            let (path, content) = self.find_target(base, rel).with_context(|| {
+                format!(
+                    "Included file \"{rel}\" not found (from {})",
+                    display(llmd, from)
+                )
+            })?;
+            if !path.starts_with(&self.root) {
+                bail!(
+                    "Included file \"{rel}\" is outside {} (from {})",
+                    self.root.display(),
+                    display(llmd, from)
+                );
+            }
+            (path, content)
+        };
+
+        let frame = (path.clone(), anchor.clone());
+        if self.stack.contains(&frame) {
+            let mut chain: Vec<String> = self.stack.iter().map(|f| describe(llmd, f)).collect();
+            chain.push(describe(llmd, &frame));
+            bail!("Include cycle detected: {}", chain.join(" → "));
+        }
+
+        if !self.files.contains(&path) && path != self.stack[0].0 {
+            self.files.push(path.clone());
+        }
+        let (_, body) = topic::split(&content);
+        let selected = match &anchor {
+            Some(a) => markdown::extract_anchor(body, a).with_context(|| {
+                format!(
+                    "Section \"#{a}\" not found in {} (included from {})",
+                    display(llmd, &path),
+                    display(llmd, from)
+                )
+            })?,
+            None => body.to_string(),
+        };
+
+        self.stack.push(frame);
+        let expanded = self.expand(&path, &selected);
+        self.stack.pop();
+        // The directive's own line ending follows the inserted text.
+        let mut expanded = expanded?;
+        if expanded.ends_with('\n') {
+            expanded.pop();
+        }
+        Ok(expanded)
+    }
+
+    /// Resolves an include target relative to `base`, trying `<rel>.md` if
+    /// the exact path does not exist. Returns the path and its content.
+    fn find_target(&self, base: &Path, rel: &str) -> Option<(PathBuf, String)> {
+        [base.join(rel), base.join(format!("{rel}.md"))]
+            .into_iter()
+            .map(|candidate| normalise(&candidate))
+            .find_map(|path| (self.read)(&path).map(|content| (path, content)))
+    }
+}
+
+/// Canonicalises `path`, or, for a file that does not exist (any more),
+/// removes its `.` and `..` components.
+fn normalise(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn display(llmd: &Path, path: &Path) -> String {
+    let llmd = normalise(llmd);
+    let path = normalise(path);
+    path.strip_prefix(&llmd)
+        .unwrap_or(&path)
+        .display()
+        .to_string()
+}
+
+fn describe(llmd: &Path, (path, anchor): &Frame) -> String {
+    match anchor {
+        Some(a) => format!("{}#{a}", display(llmd, path)),
+        None => display(llmd, path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixture::Fixture;
+
+    fn resolve_in(fx: &Fixture, rel: &str) -> Result<(String, Vec<PathBuf>)> {
+        let llmd = fx.llmd();
+        let path = llmd.join(rel);
+        resolve(&llmd, &path, &fs::read_to_string(&path).unwrap())
+    }
+
+    #[test]
+    fn test_resolve() {
+        let fx = Fixture::new(
+            "transclude",
+            &[
+                (
+                    "main.md",
+                    "A\n{{#include parts/intro}}\nB\n\\{{#include x}}\n",
+                ),
+                (
+                    "parts/intro.md",
+                    "---\ntitle: Intro\n---\nintro {{#include ../notes.md#two}}\n",
+                ),
+                ("notes.md", "# Notes\n\n## One\n\none\n\n## Two\n\ntwo\n"),
+                ("loop-a.md", "{{#include loop-b}}\n"),
+                ("loop-b.md", "{{#include loop-a.md}}\n"),
+                ("missing.md", "{{#include nowhere}}\n"),
+                ("anchor.md", "{{#include notes#three}}\n"),
+                ("escape.md", "{{#include ../secret}}\n"),
+                (
+                    "code.md",
+                    "Write `{{#include notes}}`.\n\n```md\n{{#include nowhere}}\n```\n\n\
+                     ~~~\n{{#include loop-a}}\n~~~\n{{#include parts/plain}}\n",
+                ),
+                ("parts/plain.md", "plain\n"),
+            ],
+        );
+        fs::write(fx.0.join("secret.md"), "secret\n").unwrap();
+
+        let (text, files) = resolve_in(&fx, "main.md").unwrap();
+        assert_eq!(text, "A\nintro ## Two\n\ntwo\nB\n{{#include x}}\n");
+        let llmd = fx.llmd();
+        assert_eq!(
+            files,
+            vec![llmd.join("parts/intro.md"), llmd.join("notes.md")]
+        );
+        let cycle = resolve_in(&fx, "loop-a.md").unwrap_err().to_string();
+        assert!(
+            cycle.contains("loop-a.md → loop-b.md → loop-a.md"),
+            "{cycle}"
+        );
+        let missing = resolve_in(&fx, "missing.md").unwrap_err().to_string();
+        assert!(missing.contains("\"nowhere\" not found"), "{missing}");
+        let anchor = resolve_in(&fx, "anchor.md").unwrap_err().to_string();
+        assert!(anchor.contains("\"#three\" not found"), "{anchor}");
+        let outside = resolve_in(&fx, "escape.md").unwrap_err().to_string();
+        assert!(outside.contains("outside"), "{outside}");
+        // Only the directive outside code is expanded.
+        let (text, _) = resolve_in(&fx, "code.md").unwrap();
+        assert_eq!(
+            text,
+            "Write `{{#include notes}}`.\n\n```md\n{{#include nowhere}}\n```\n\n\
+             ~~~\n{{#include loop-a}}\n~~~\nplain\n"
+        );
+    }
+
+    #[test]
+    fn test_resolve_with() {
+        let fx = Fixture::new("transclude-with", &[("main.md", "")]);
+        let llmd = fx.llmd();
+        // An older version of an included file that no longer exists on disk.
+        let old = std::collections::HashMap::from([(llmd.join("gone.md"), "old text\n")]);
+        let read = |p: &Path| old.get(p).map(|s| s.to_string());
+        let (text, files) = resolve_with(
+            &llmd,
+            &llmd.join("main.md"),
+            "{{#include sub/../gone}}\n",
+            &read,
+        )
+        .unwrap();
+        assert_eq!(text, "old text\n");
+        assert_eq!(files, vec![llmd.join("gone.md")]);
+    }
+}
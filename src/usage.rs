@@ -0,0 +1,84 @@
+//! Opt-in local usage log: `.llmd/.usage.jsonl`.
+//!
+//! When `usage.log` is set in `.llmd/llmd.json`, `read`, `compose` and
+//! `search` append one JSON line per run recording what they touched, so
+//! `llmd usage report` can show which sections agents actually use. Nothing
+//! leaves the machine; the log is never read by other commands.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::issues::file_ops::now_iso;
+use crate::llmd_dir;
+use crate::tokenizer::Tokenizer;
+
+/// Name of the log inside .llmd/.
+pub const FILE_NAME: &str = ".usage.jsonl";
+
+/// One line of the usage log.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// ISO 8601 UTC timestamp.
+    pub ts: String,
+    /// `read`, `compose` or `search`.
+    pub command: String,
+    /// Section labels (`file > Heading`) or file labels, as shown by `llmd index`.
+    pub touched: Vec<String>,
+    /// Tokens of the output, counted with the configured tokenizer.
+    pub tokens: usize,
+}
+
+/// Returns the path to `.usage.jsonl` inside `llmd`.
+pub fn path(llmd: &Path) -> PathBuf {
+    llmd.join(FILE_NAME)
+}
+
+/// Appends an entry if `usage.log` is enabled. Logging must never break the
+/// command being logged, so failures are reported as warnings.
+pub fn record(llmd: &Path, command: &str, touched: Vec<String>, output: &str) {
+    if !llmd_dir::config(llmd).is_ok_and(|c| c.usage.log) {
+        return;
+    }
+    if let Err(e) = append(llmd, command, touched, output) {
+        eprintln!("Warning: could not write the usage log: {e:#}");
+    }
+}
+
+fn append(llmd: &Path, command: &str, touched: Vec<String>, output: &str) -> Result<()> {
+    let entry = Entry {
+        ts: now_iso(),
+        command: command.to_string(),
+        touched,
+        tokens: Tokenizer::for_project(llmd)?.count(output),
+    };
+    let path = path(llmd);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Cannot open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)
+        .with_context(|| format!("Cannot write {}", path.display()))
+}
+
+/// Reads every entry in the log, skipping (and counting) malformed lines.
+pub fn load(llmd: &Path) -> Result<(Vec<Entry>, usize)> {
+    let path = path(llmd);
+    if !path.is_file() {
+        return Ok((Vec::new(), 0));
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => skipped += 1,
+        }
+    }
+    Ok((entries, skipped))
+}